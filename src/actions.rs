@@ -1,4 +1,6 @@
-use std::process::Command;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
 
 pub enum SimpleAction {
     ListOutputs,
@@ -30,6 +32,19 @@ pub struct SimpleCommandOutput {
     pub status_code: Option<i32>,
 }
 
+// Hands a file or folder to the desktop's default application.
+pub fn open_with_default_app(path: &Path) -> Result<(), String> {
+    let mut child = Command::new("xdg-open")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Failed to run xdg-open {}: {err}", path.display()))?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
 pub fn run_simple_command(args: &[&str]) -> Result<SimpleCommandOutput, String> {
     let output = Command::new("wf-recorder")
         .args(args)
@@ -1,4 +1,4 @@
 mod state;
 mod view;
 
-pub use state::RecorderApp;
+pub use state::{RecorderApp, SidebarState};
@@ -1,25 +1,58 @@
 use chrono::Local;
+use serde::{Deserialize, Serialize};
 use std::io::{BufRead, BufReader};
 use std::process::{Command, ExitStatus, Stdio};
 use std::sync::{
     Arc, Mutex,
     atomic::{AtomicBool, Ordering},
-    mpsc::{self, Receiver, TryRecvError},
+    mpsc::{self, Receiver, Sender, TryRecvError},
 };
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{fs, path::Path};
 
-#[cfg(unix)]
-use std::os::unix::process::ExitStatusExt;
-
-use crate::actions::{SimpleAction, run_simple_command};
-use crate::config::{CaptureMode, RecorderConfig};
+use crate::actions::{SimpleAction, open_with_default_app, run_simple_command};
+use crate::capabilities::{
+    FfmpegCapabilities, detect_ffmpeg_capabilities, load_cached_capabilities,
+    save_cached_capabilities,
+};
+use crate::compat::{check_compatibility, first_error, suggest_vaapi_setup};
+use crate::config::{CaptureMode, CollisionPolicy, RecorderConfig, TemplateContext};
+use crate::control::{
+    ControlMessage, ControlRequest, ControlResponse, ControlServer, ControlStatus, socket_path,
+};
 use crate::discovery::{
-    detect_audio_devices, detect_output_geometry, detect_outputs, detect_windows,
+    detect_audio_devices, detect_outputs, detect_render_nodes, detect_screen_geometry_override,
+    detect_windows,
+};
+use crate::export::{ExportPreset, export_output, plan_export, run_export};
+use crate::history::{
+    CaptureDetails, RecordingEntry, RecordingHistory, format_file_size, load_history, save_history,
+};
+use crate::instance::{
+    RecorderPidFile, RecorderPidRecord, SessionLock, find_stray_recorders, forget_recorder,
+    is_recorder_process,
 };
 use crate::models::{
-    AudioDevice, LogEntry, LogSource, OutputChoice, RecorderProcess, RecorderStatus, WindowChoice,
+    AudioDevice, LogEntry, LogSource, OutputChoice, PendingStart, ProcessExit, RecorderHandle,
+    RecorderProcess, RecorderStatus, RecordingSession, RenderNode, Rotation, WindowChoice,
+};
+use crate::profiles::ProfileStore;
+use crate::recorder::{
+    RestartPolicy, StopProgress, StopStage, concat_segments, format_exit_status,
+    format_process_exit, send_stop_signal, shell_preview, stop_with_parent, with_output_file,
 };
+use crate::recovery::{Recovery, intermediate_path, recover, remux};
+use crate::schedule::{RecordingLimits, delay_until, format_duration};
+use crate::session_log::{SessionLog, logs_dir};
+use crate::settings::{Settings, UiSettings, load_settings, save_settings};
+use crate::sidecar::{RecordingMetadata, prefetch_wf_recorder_version};
+use crate::trim::{CutMode, derived_path, format_timestamp, thumbnail, trim};
+use crate::verify::{Expectations, probe, verify_recording};
+
+const SETTINGS_SAVE_DELAY: Duration = Duration::from_millis(750);
+// The resolved path only changes with the clock between edits, and `$n`
+// probes the filesystem, so the preview is not recomputed every frame.
+const OUTPUT_PREVIEW_REFRESH: Duration = Duration::from_secs(1);
 
 #[derive(Clone, Copy, PartialEq)]
 pub(super) enum Section {
@@ -27,6 +60,7 @@ pub(super) enum Section {
     VideoEncoding,
     AudioRecording,
     ToolsDiagnostics,
+    Library,
 }
 
 #[derive(Clone)]
@@ -61,12 +95,194 @@ pub struct RecorderApp {
     pub(super) audio_devices_loading: bool,
     pub(super) audio_devices_error: Option<String>,
     pub(super) audio_devices_receiver: Option<Receiver<Result<Vec<AudioDevice>, String>>>,
+    pub(super) ffmpeg_capabilities: FfmpegCapabilities,
+    pub(super) ffmpeg_capabilities_loading: bool,
+    pub(super) ffmpeg_capabilities_error: Option<String>,
+    pub(super) ffmpeg_capabilities_receiver: Option<Receiver<Result<FfmpegCapabilities, String>>>,
     pub(super) dark_theme: bool,
     pub(super) sidebar_state: SidebarState,
+    pub(super) allow_concurrent_sessions: bool,
+    pub(super) render_nodes: Vec<RenderNode>,
+    pub(super) profiles: ProfileStore,
+    pub(super) profile_dialog: Option<ProfileDialog>,
+    pub(super) saved_settings: Settings,
+    pub(super) settings_changed_at: Option<Instant>,
+    pub(super) control_server: Option<ControlServer>,
+    pub(super) control_receiver: Option<Receiver<ControlMessage>>,
+    pub(super) focus_requested: bool,
+    pub(super) stray_recorders: Vec<RecorderPidRecord>,
+    pub(super) close_dialog_open: bool,
+    pub(super) quit_after_stop: bool,
+    pub(super) concat_receiver: Option<Receiver<Result<(String, String), String>>>,
+    pub(super) scheduled_start: String,
+    pub(super) collision_prompt: Option<String>,
+    pub(super) history: RecordingHistory,
+    pub(super) pending_history: Option<FinishedRecording>,
+    pub(super) library_rename: Option<(usize, String)>,
+    pub(super) library_delete: Option<usize>,
+    pub(super) log_viewer: Option<(String, String)>,
+    pub(super) verify_sender: Sender<(String, Vec<String>)>,
+    pub(super) verify_receiver: Receiver<(String, Vec<String>)>,
+    pub(super) verify_pending: usize,
+    pub(super) recovery_path: String,
+    pub(super) recovery_receiver: Option<Receiver<(String, Result<Recovery, String>)>>,
+    pub(super) recovery_result: Option<Result<String, String>>,
+    pub(super) trim_dialog: Option<TrimDialog>,
+    pub(super) export_dialog: Option<ExportDialog>,
+    // Image URIs the view should release from the egui cache.
+    pub(super) stale_thumbnails: Vec<String>,
+    output_preview: Option<OutputPreview>,
+}
+
+pub(super) struct OutputPreview {
+    config: RecorderConfig,
+    profile: String,
+    resolved_at: Instant,
+    pub(super) file: Result<String, String>,
+    pub(super) command: Result<String, String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum ProfileDialogKind {
+    Create,
+    Duplicate,
+    Rename,
+}
+
+// A finished output file on its way into the library (and its sidecar).
+pub(super) struct FinishedRecording {
+    entry: RecordingEntry,
+    metadata: Option<RecordingMetadata>,
+    expectations: Option<Expectations>,
+}
+
+impl FinishedRecording {
+    // The file only settles once segments are joined or remuxed.
+    fn set_file(&mut self, file: &str) {
+        self.entry.file = file.to_string();
+        self.entry.refresh();
+        if let Some(metadata) = &mut self.metadata {
+            metadata.file = file.to_string();
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum TrimEdge {
+    Start,
+    End,
+}
+
+enum TrimUpdate {
+    Duration(f64),
+    Thumbnail(TrimEdge, f64, Result<Vec<u8>, String>),
+    Finished(Result<CutMode, String>),
+}
+
+pub(super) struct Thumbnail {
+    pub(super) uri: String,
+    pub(super) png: Arc<[u8]>,
+}
+
+pub(super) struct TrimDialog {
+    pub(super) source: String,
+    pub(super) output: String,
+    pub(super) duration: Option<f64>,
+    pub(super) start: f64,
+    pub(super) end: f64,
+    pub(super) exact: bool,
+    pub(super) running: bool,
+    pub(super) error: Option<String>,
+    // Newest frame at each edge, or why there is none.
+    pub(super) thumbnails: [Option<Result<Thumbnail, String>>; 2],
+    pending_thumbnails: [Option<f64>; 2],
+    sender: Sender<TrimUpdate>,
+    receiver: Receiver<TrimUpdate>,
+}
+
+impl TrimDialog {
+    fn new(source: String, duration: f64) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let file = source.clone();
+        let probe_sender = sender.clone();
+        std::thread::spawn(move || {
+            let duration = probe(&file).ok().and_then(|report| report.duration);
+            let _ = probe_sender.send(TrimUpdate::Duration(duration.unwrap_or(0.0)));
+        });
+        Self {
+            output: derived_path(&source, "trimmed", None),
+            source,
+            duration: None,
+            start: 0.0,
+            end: duration,
+            exact: false,
+            running: false,
+            error: None,
+            thumbnails: [None, None],
+            pending_thumbnails: [None, None],
+            sender,
+            receiver,
+        }
+    }
+
+    pub(super) fn busy(&self) -> bool {
+        self.running
+            || self.duration.is_none()
+            || self.pending_thumbnails.iter().any(Option::is_some)
+    }
+
+    pub(super) fn request_thumbnail(&mut self, edge: TrimEdge) {
+        let at = match edge {
+            TrimEdge::Start => self.start,
+            // The very last timestamp usually has no frame left to decode.
+            TrimEdge::End => (self.end - 0.1).max(0.0),
+        };
+        self.pending_thumbnails[edge as usize] = Some(at);
+        let file = self.source.clone();
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(TrimUpdate::Thumbnail(edge, at, thumbnail(&file, at)));
+        });
+    }
+}
+
+enum ExportUpdate {
+    Progress(f32),
+    Finished(Result<u64, String>),
 }
 
-#[derive(Clone, Copy)]
-pub(super) enum SidebarState {
+pub(super) struct ExportDialog {
+    pub(super) source: String,
+    pub(super) preset: ExportPreset,
+    pub(super) target_mb: f64,
+    pub(super) output: String,
+    // 0.0..=1.0 across all passes while ffmpeg runs.
+    pub(super) progress: Option<f32>,
+    pub(super) error: Option<String>,
+    duration: f64,
+    cancel: Arc<AtomicBool>,
+    receiver: Option<Receiver<ExportUpdate>>,
+}
+
+impl ExportDialog {
+    pub(super) fn running(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    pub(super) fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+pub(super) struct ProfileDialog {
+    pub(super) kind: ProfileDialogKind,
+    pub(super) name: String,
+    pub(super) error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidebarState {
     Expanded,
     Compact,
     Hidden,
@@ -88,15 +304,25 @@ impl RecorderApp {
     }
 
     pub fn new() -> Self {
+        let (mut settings, settings_error) = match load_settings() {
+            Ok(settings) => (settings, None),
+            Err(err) => (Settings::default(), Some(err)),
+        };
+        settings.profiles.active = settings.profiles.default_profile.clone();
+        let (verify_sender, verify_receiver) = mpsc::channel();
+        let (history, history_error) = match load_history() {
+            Ok(history) => (history, None),
+            Err(err) => (RecordingHistory::default(), Some(err)),
+        };
         let mut app = Self {
-            config: RecorderConfig::default(),
+            config: settings.profiles.active_config(),
             status: RecorderStatus::default(),
             current_section: Section::CaptureBasics,
             log_entries: Arc::new(Mutex::new(Vec::new())),
             log_buffer: Arc::new(Mutex::new(String::new())),
             log_dirty: Arc::new(AtomicBool::new(true)),
             log_display: String::new(),
-            last_error: None,
+            last_error: settings_error.or(history_error),
             last_action_output: None,
             last_recording_summary: None,
             available_outputs: Vec::new(),
@@ -111,28 +337,160 @@ impl RecorderApp {
             audio_devices_loading: false,
             audio_devices_error: None,
             audio_devices_receiver: None,
-            dark_theme: true,
-            sidebar_state: SidebarState::Expanded,
+            ffmpeg_capabilities: load_cached_capabilities().unwrap_or_default(),
+            ffmpeg_capabilities_loading: false,
+            ffmpeg_capabilities_error: None,
+            ffmpeg_capabilities_receiver: None,
+            dark_theme: settings.ui.dark_theme,
+            sidebar_state: settings.ui.sidebar_state,
+            allow_concurrent_sessions: settings.ui.allow_concurrent_sessions,
+            render_nodes: Vec::new(),
+            profiles: settings.profiles.clone(),
+            profile_dialog: None,
+            saved_settings: settings,
+            settings_changed_at: None,
+            control_server: None,
+            control_receiver: None,
+            focus_requested: false,
+            stray_recorders: find_stray_recorders(),
+            close_dialog_open: false,
+            quit_after_stop: false,
+            concat_receiver: None,
+            scheduled_start: String::new(),
+            collision_prompt: None,
+            history,
+            pending_history: None,
+            library_rename: None,
+            library_delete: None,
+            log_viewer: None,
+            verify_sender,
+            verify_receiver,
+            verify_pending: 0,
+            recovery_path: String::new(),
+            recovery_receiver: None,
+            recovery_result: None,
+            trim_dialog: None,
+            output_preview: None,
+            export_dialog: None,
+            stale_thumbnails: Vec::new(),
         };
         app.request_output_refresh();
         app.request_window_refresh();
         app.request_audio_refresh();
+        app.request_ffmpeg_capabilities_refresh();
+        app.refresh_render_nodes();
 
         app
     }
 
+    pub fn start_control_server(&mut self, ctx: eframe::egui::Context) {
+        match ControlServer::start(&socket_path(), move || ctx.request_repaint()) {
+            Ok((server, receiver)) => {
+                self.control_server = Some(server);
+                self.control_receiver = Some(receiver);
+            }
+            Err(err) => {
+                self.last_error = Some(format!("Remote control is unavailable: {err}"));
+            }
+        }
+    }
+
     pub(super) fn start_recording(&mut self) {
-        let screen_geometry_override = self.detect_screen_geometry_override();
-        let (args, output_file) = match self
-            .config
-            .build_command_args(None, screen_geometry_override)
+        if let Some(err) = first_error(&check_compatibility(&self.config)) {
+            self.last_error = Some(err.to_string());
+            return;
+        }
+        let scheduled_for = self.scheduled_start.trim().to_string();
+        let delay = if scheduled_for.is_empty() {
+            Duration::from_secs(self.config.countdown_secs)
+        } else {
+            match delay_until(&scheduled_for, Local::now().naive_local()) {
+                Ok(delay) => delay,
+                Err(err) => {
+                    self.last_error = Some(err);
+                    return;
+                }
+            }
+        };
+        if delay.is_zero() {
+            self.begin_recording(None);
+            return;
+        }
+        self.status = RecorderStatus::Scheduled(PendingStart {
+            start_at: Instant::now() + delay,
+            scheduled_for: (!scheduled_for.is_empty()).then_some(scheduled_for),
+        });
+        self.last_error = None;
+        self.last_recording_summary = None;
+    }
+
+    pub(super) fn start_now(&mut self) {
+        if let RecorderStatus::Scheduled(_) = std::mem::take(&mut self.status) {
+            self.begin_recording(None);
+        }
+    }
+
+    // `None` cancels the start that found its output file already taken.
+    pub(super) fn resolve_collision(&mut self, policy: Option<CollisionPolicy>) {
+        if self.collision_prompt.take().is_some()
+            && let Some(policy) = policy
         {
+            self.begin_recording(Some(policy));
+        }
+    }
+
+    fn begin_recording(&mut self, collision_policy: Option<CollisionPolicy>) {
+        let screen_geometry_override = detect_screen_geometry_override(&self.config);
+        let context = TemplateContext::now(&self.profiles.active);
+        let resolved = self
+            .config
+            .build_command_args(&context, screen_geometry_override)
+            .and_then(|(args, output_file)| {
+                Ok((
+                    args,
+                    output_file,
+                    self.config.resolve_output_pattern(&context)?,
+                ))
+            });
+        let (mut args, mut output_file, mut pattern) = match resolved {
             Ok(result) => result,
             Err(err) => {
                 self.last_error = Some(err);
                 return;
             }
         };
+        if !self.config.overwrite
+            && let Some(existing) = self.config.existing_output(&pattern)
+        {
+            match collision_policy.unwrap_or(self.config.collision_policy) {
+                CollisionPolicy::Ask => {
+                    self.collision_prompt = Some(existing);
+                    return;
+                }
+                CollisionPolicy::Fail => {
+                    self.last_error = Some(format!("{existing} already exists."));
+                    return;
+                }
+                CollisionPolicy::Overwrite => args.push("--overwrite".to_string()),
+                CollisionPolicy::Suffix => {
+                    pattern = self.config.free_output_pattern(&pattern);
+                    output_file = self.config.first_file(&pattern);
+                    args = with_output_file(&args, &output_file);
+                }
+            }
+        }
+        // The collision check above is about the final file; the Matroska
+        // intermediate gets a free name of its own.
+        let mut remux_to = None;
+        if self.config.record_as_mkv
+            && !self.config.segmenting()
+            && self.config.muxer.trim().is_empty()
+            && let Some(intermediate) = intermediate_path(&output_file)
+        {
+            args = with_output_file(&args, &intermediate);
+            remux_to = Some(std::mem::replace(&mut output_file, intermediate));
+        }
+        #[allow(clippy::collapsible_if)]
         if let Some(parent) = Path::new(&output_file).parent() {
             if let Err(err) = fs::create_dir_all(parent) {
                 self.last_error = Some(format!(
@@ -143,10 +501,83 @@ impl RecorderApp {
             }
         }
 
+        let session_lock = if self.allow_concurrent_sessions {
+            SessionLock::acquire().ok()
+        } else {
+            match SessionLock::acquire() {
+                Ok(lock) => Some(lock),
+                Err(err) => {
+                    self.last_error = Some(format!(
+                        "{err} Enable \"Allow concurrent recordings\" under Tools & Diagnostics to record anyway."
+                    ));
+                    return;
+                }
+            }
+        };
+
+        let mut session = RecordingSession::new(args, output_file.clone(), session_lock);
+        session.limits = RecordingLimits::from_config(&self.config);
+        session.restart_policy = RestartPolicy::from_config(&self.config);
+        session.started = context.time;
+        session.details = CaptureDetails::from_config(&self.config, &self.profiles.active);
+        match SessionLog::create(&output_file, context.time) {
+            Ok(log) => session.log = Some(Arc::new(log)),
+            Err(err) => self.log_note(None, format!("Session log unavailable: {err}")),
+        }
+        session.config = Some(self.config.clone());
+        session.remux_to = remux_to;
+        if self.config.write_metadata {
+            prefetch_wf_recorder_version();
+        }
+        if self.config.segmenting() {
+            session.rotation = Some(Rotation::new(
+                RecordingLimits::for_segments(&self.config),
+                pattern,
+            ));
+        }
+        if self.spawn_segment(session, output_file).is_ok() {
+            self.last_recording_summary = None;
+        }
+    }
+
+    pub(super) fn pause_recording(&mut self) {
+        if let RecorderStatus::Running(process) = &mut self.status
+            && process.session.can_pause()
+        {
+            process.session.pause_requested = true;
+            self.stop_recording();
+        }
+    }
+
+    pub(super) fn resume_recording(&mut self) {
+        let RecorderStatus::Paused(mut session) = std::mem::take(&mut self.status) else {
+            return;
+        };
+        session.restart_at = None;
+        let segment_file = session.next_segment_file();
+        if let Err(session) = self.spawn_segment(session, segment_file) {
+            self.status = RecorderStatus::Paused(*session);
+        }
+    }
+
+    fn spawn_segment(
+        &mut self,
+        session: RecordingSession,
+        segment_file: String,
+    ) -> Result<(), Box<RecordingSession>> {
         let mut command = Command::new("wf-recorder");
-        command.args(&args);
+        let args = with_output_file(&session.args, &segment_file);
+        if let Some(log) = &session.log {
+            log.write(
+                LogSource::Supervisor,
+                &format!("Starting {}", shell_preview(args.clone())),
+            );
+        }
+        command.args(args);
+        command.stdin(Stdio::null());
         command.stdout(Stdio::piped());
         command.stderr(Stdio::piped());
+        stop_with_parent(&mut command);
 
         match command.spawn() {
             Ok(mut child) => {
@@ -159,6 +590,7 @@ impl RecorderApp {
                         Arc::clone(&self.log_entries),
                         Arc::clone(&self.log_buffer),
                         Arc::clone(&self.log_dirty),
+                        session.log.clone(),
                         LogSource::Stdout,
                     )
                 });
@@ -168,107 +600,871 @@ impl RecorderApp {
                         Arc::clone(&self.log_entries),
                         Arc::clone(&self.log_buffer),
                         Arc::clone(&self.log_dirty),
+                        session.log.clone(),
                         LogSource::Stderr,
                     )
                 });
 
+                let (pid_file, pid_file_error) = match RecorderPidFile::create(
+                    &RecorderPidRecord::new(child.id(), &session.output_file),
+                ) {
+                    Ok(pid_file) => (Some(pid_file), None),
+                    Err(err) => (None, Some(err)),
+                };
+
                 self.status = RecorderStatus::Running(RecorderProcess {
-                    child,
+                    handle: RecorderHandle::Spawned(child),
                     stdout_join,
                     stderr_join,
                     started_at: Instant::now(),
-                    output_file,
+                    segment_file,
+                    pid_file,
+                    session,
                 });
-                self.last_error = None;
-                self.last_recording_summary = None;
+                self.last_error = pid_file_error;
+                Ok(())
+            }
+            Err(err) => {
+                self.last_error = Some(format!("Failed to start wf-recorder: {err}"));
+                Err(Box::new(session))
+            }
+        }
+    }
+
+    pub(super) fn stop_recording(&mut self) {
+        self.begin_stop(StopStage::Interrupt);
+    }
+
+    pub(super) fn force_stop_recording(&mut self) {
+        self.begin_stop(StopStage::Kill);
+    }
+
+    fn begin_stop(&mut self, stage: StopStage) {
+        let process = match std::mem::take(&mut self.status) {
+            RecorderStatus::Running(process) => process,
+            RecorderStatus::Stopping(mut process, progress)
+                if progress.stage != StopStage::Kill =>
+            {
+                process.session.cancel_transitions();
+                if stage != StopStage::Kill {
+                    self.status = RecorderStatus::Stopping(process, progress);
+                    return;
+                }
+                process
+            }
+            RecorderStatus::Paused(session) => {
+                self.finish_session(session, None);
+                return;
+            }
+            RecorderStatus::Scheduled(_) => {
+                self.last_recording_summary = Some("Scheduled recording cancelled.".to_string());
+                return;
+            }
+            status => {
+                self.status = status;
+                return;
+            }
+        };
+
+        let timeout = Duration::from_secs(self.config.stop_timeout_secs);
+        if let Err(err) = send_stop_signal(process.handle.id(), stage) {
+            self.last_error = Some(format!(
+                "Failed to send {} to wf-recorder: {err}",
+                stage.signal_name()
+            ));
+        }
+        self.status = RecorderStatus::Stopping(process, StopProgress::new(stage, timeout));
+    }
+
+    pub(super) fn poll_process(&mut self) {
+        let current_status = std::mem::take(&mut self.status);
+        let (mut process, progress) = match current_status {
+            RecorderStatus::Scheduled(pending) if pending.is_due(Instant::now()) => {
+                self.begin_recording(None);
+                return;
+            }
+            RecorderStatus::Paused(session) if session.restart_due(Instant::now()) => {
+                self.status = RecorderStatus::Paused(session);
+                self.resume_recording();
+                return;
+            }
+            RecorderStatus::Running(process) => (process, None),
+            RecorderStatus::Stopping(process, progress) => (process, Some(progress)),
+            status => {
+                self.status = status;
+                return;
+            }
+        };
+
+        let exit = match process.handle.try_wait() {
+            Ok(Some(exit)) => exit,
+            Ok(None) => {
+                self.status = match progress {
+                    Some(mut progress) => {
+                        if let Err(err) = progress.escalate(process.handle.id()) {
+                            self.last_error =
+                                Some(format!("Failed to escalate wf-recorder stop: {err}"));
+                        }
+                        RecorderStatus::Stopping(process, progress)
+                    }
+                    None => RecorderStatus::Running(process),
+                };
+                self.enforce_limits();
+                return;
+            }
+            Err(err) => {
+                self.last_error = Some(format!("Failed to poll wf-recorder status: {err}"));
+                self.status = match progress {
+                    Some(progress) => RecorderStatus::Stopping(process, progress),
+                    None => RecorderStatus::Running(process),
+                };
+                return;
+            }
+        };
+
+        process.finish();
+        let RecorderProcess {
+            started_at,
+            segment_file,
+            mut session,
+            ..
+        } = process;
+        let duration = started_at.elapsed();
+        session.recorded += duration;
+        let segment_exists = Path::new(&segment_file).exists();
+        session.last_exit = format_process_exit(&exit)
+            .trim_start_matches(", ")
+            .to_string();
+        session.unclean_exit = progress
+            .as_ref()
+            .is_some_and(|progress| progress.stage == StopStage::Kill)
+            || !matches!(&exit, ProcessExit::Exited(status) if status.success());
+
+        if progress.is_none()
+            && !session.pause_requested
+            && session.can_pause()
+            && let ProcessExit::Exited(status) = &exit
+            && !status.success()
+        {
+            self.schedule_restart(&mut session, *status, duration);
+        }
+
+        if session.rotation.is_some() {
+            let rotate = session
+                .rotation
+                .as_mut()
+                .is_some_and(|rotation| std::mem::take(&mut rotation.rotate_requested));
+            if segment_exists {
+                let chunk_started = Local::now()
+                    - chrono::Duration::from_std(duration)
+                        .unwrap_or_else(|_| chrono::Duration::zero());
+                let record = finished_recording(&session, &segment_file, chunk_started, duration);
+                self.record_history(record);
+                if let Some(rotation) = &mut session.rotation {
+                    rotation.chunks.push(segment_file);
+                }
+            } else {
+                self.last_error = Some(format!(
+                    "wf-recorder exited{} without writing {segment_file}.",
+                    format_process_exit(&exit)
+                ));
+            }
+            if std::mem::take(&mut session.pause_requested) {
+                self.status = RecorderStatus::Paused(session);
+            } else if rotate && segment_exists {
+                let next_file = session.next_segment_file();
+                if let Err(session) = self.spawn_segment(session, next_file) {
+                    self.finish_session(*session, progress.as_ref());
+                }
+            } else {
+                self.finish_session(session, progress.as_ref());
+            }
+            return;
+        }
+
+        if session.pause_requested {
+            session.pause_requested = false;
+            if segment_exists {
+                if segment_file == session.output_file {
+                    let first_part = session.next_segment_file();
+                    match fs::rename(&segment_file, &first_part) {
+                        Ok(()) => session.parts.push(first_part),
+                        Err(err) => {
+                            self.last_error =
+                                Some(format!("Failed to keep the paused segment: {err}"))
+                        }
+                    }
+                } else {
+                    session.parts.push(segment_file);
+                }
+            } else {
+                self.last_error = Some(format!(
+                    "wf-recorder exited{} without writing segment {}.",
+                    format_process_exit(&exit),
+                    session.parts.len() + 1
+                ));
+            }
+            self.status = RecorderStatus::Paused(session);
+            return;
+        }
+
+        if !session.parts.is_empty()
+            || segment_file != session.output_file
+            || (session.remux_to.is_some() && segment_exists)
+        {
+            if segment_exists {
+                session.parts.push(segment_file);
+            }
+            self.finish_session(session, progress.as_ref());
+            return;
+        }
+
+        let exit_suffix = format_process_exit(&exit);
+        let mut summary = if segment_exists {
+            format!(
+                "Saved to {}\nwf-recorder exited after {:.1} seconds{}",
+                session.output_file,
+                duration.as_secs_f32(),
+                exit_suffix
+            )
+        } else {
+            let mut message = format!(
+                "wf-recorder exited after {:.1} seconds{} but no file was created at {}.",
+                duration.as_secs_f32(),
+                exit_suffix,
+                session.output_file
+            );
+            if let Some(log_tail) = self.recent_log_tail(8) {
+                message.push_str("\nRecent wf-recorder output:\n");
+                message.push_str(&log_tail);
+            } else {
+                message.push_str(
+                    "\nNo wf-recorder output was captured. Use Tools & Diagnostics → wf-recorder log for details.",
+                );
+            }
+            message
+        };
+        append_session_notes(&mut summary, &session, progress.as_ref());
+        if segment_exists {
+            self.record_history(session_record(&session));
+        } else {
+            self.last_error
+                .get_or_insert_with(|| "Recording did not produce an output file.".to_string());
+        }
+        self.last_recording_summary = Some(summary);
+    }
+
+    // An unexpected exit becomes an automatic pause with a scheduled resume, so
+    // the restarted run lands in a new segment exactly like a manual resume.
+    fn schedule_restart(
+        &mut self,
+        session: &mut RecordingSession,
+        status: ExitStatus,
+        ran: Duration,
+    ) {
+        let Some(policy) = session.restart_policy else {
+            return;
+        };
+        let attempt = policy.next_attempt(session.restarts, ran);
+        match policy.delay_for(attempt) {
+            Some(delay) => {
+                session.restarts = attempt;
+                session.total_restarts += 1;
+                session.restart_at = Some(Instant::now() + delay);
+                session.pause_requested = true;
+                self.log_note(
+                    session.log.as_deref(),
+                    format!(
+                        "wf-recorder exited{}; restarting in {} (attempt {attempt} of {}).",
+                        format_exit_status(status),
+                        format_duration(delay),
+                        policy.max_restarts
+                    ),
+                );
+            }
+            None => self.log_note(
+                session.log.as_deref(),
+                format!(
+                    "wf-recorder exited{}; giving up after {} restarts.",
+                    format_exit_status(status),
+                    policy.max_restarts
+                ),
+            ),
+        }
+    }
+
+    // Auto-stop goes through the normal graceful stop so the file is finalized.
+    fn enforce_limits(&mut self) {
+        let RecorderStatus::Running(process) = &mut self.status else {
+            return;
+        };
+        let limits = process.session.limits;
+        let chunk_limits = process
+            .session
+            .rotation
+            .as_ref()
+            .map(|rotation| rotation.limits)
+            .unwrap_or_default();
+        if limits.is_unlimited() && chunk_limits.is_unlimited() {
+            return;
+        }
+        let bytes = if limits.max_bytes.is_some() {
+            process.recorded_bytes()
+        } else {
+            0
+        };
+        if let Some(reason) = limits.exceeded(process.elapsed(), bytes) {
+            process.session.auto_stop_reason = Some(reason);
+            self.stop_recording();
+            return;
+        }
+
+        let chunk_bytes = if chunk_limits.max_bytes.is_some() {
+            fs::metadata(&process.segment_file)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        if chunk_limits
+            .exceeded(process.started_at.elapsed(), chunk_bytes)
+            .is_some()
+            && let Some(rotation) = &mut process.session.rotation
+        {
+            rotation.rotate_requested = true;
+            self.stop_recording();
+        }
+    }
+
+    fn finish_session(&mut self, session: RecordingSession, progress: Option<&StopProgress>) {
+        if let Some(rotation) = &session.rotation {
+            let mut summary = format!(
+                "Recorded {:.1} seconds in {} chunks:",
+                session.recorded.as_secs_f32(),
+                rotation.chunks.len()
+            );
+            for chunk in &rotation.chunks {
+                summary.push_str("\n  ");
+                summary.push_str(chunk);
+            }
+            append_session_notes(&mut summary, &session, progress);
+            if rotation.chunks.is_empty() {
+                self.last_error = Some("The segmented recording produced no files.".to_string());
+            }
+            self.last_recording_summary = Some(summary);
+            return;
+        }
+
+        let mut summary = match session.parts.len() {
+            1 => format!("Recorded {:.1} seconds.", session.recorded.as_secs_f32()),
+            count => format!(
+                "Recorded {:.1} seconds in {count} segments.",
+                session.recorded.as_secs_f32()
+            ),
+        };
+        append_session_notes(&mut summary, &session, progress);
+        let remux_to = match &session.remux_to {
+            Some(target) if session.unclean_exit => {
+                summary.push_str(&format!(
+                    "\nwf-recorder did not stop cleanly, so the Matroska capture was kept instead of being remuxed to {target}."
+                ));
+                None
+            }
+            target => target.clone(),
+        };
+
+        match session.parts.as_slice() {
+            [] => {
+                self.last_error = Some("The paused recording has no segments to save.".to_string());
+                self.last_recording_summary = Some(summary);
+            }
+            [only] if session.remux_to.is_none() => {
+                match fs::rename(only, &session.output_file) {
+                    Ok(()) => {
+                        summary = format!("Saved to {}\n{summary}", session.output_file);
+                        self.record_history(session_record(&session));
+                    }
+                    Err(err) => {
+                        self.last_error = Some(format!(
+                            "Failed to move {only} to {}: {err}",
+                            session.output_file
+                        ))
+                    }
+                }
+                self.last_recording_summary = Some(summary);
+            }
+            parts => {
+                let parts = parts.to_vec();
+                let output_file = session.output_file.clone();
+                self.pending_history = Some(session_record(&session));
+                let (tx, rx) = mpsc::channel();
+                let target = remux_to.as_deref().unwrap_or(&output_file);
+                self.last_recording_summary = Some(match parts.len() {
+                    1 => format!("Remuxing into {target}…\n{summary}"),
+                    count => format!("Joining {count} segments into {target}…\n{summary}"),
+                });
+                std::thread::spawn(move || {
+                    let result = finalize_parts(&parts, &output_file, remux_to.as_deref(), summary);
+                    let _ = tx.send(result);
+                });
+                self.concat_receiver = Some(rx);
+            }
+        }
+    }
+
+    fn record_history(&mut self, record: FinishedRecording) {
+        if let Some(metadata) = &record.metadata
+            && let Err(err) = metadata.write()
+        {
+            self.last_error = Some(err);
+        }
+        if let Some(expectations) = record.expectations {
+            let file = record.entry.file.clone();
+            let sender = self.verify_sender.clone();
+            self.verify_pending += 1;
+            std::thread::spawn(move || {
+                let warnings = verify_recording(&file, &expectations);
+                let _ = sender.send((file, warnings));
+            });
+        }
+        self.add_recording(record.entry);
+    }
+
+    fn add_recording(&mut self, entry: RecordingEntry) {
+        // Pending library edits refer to entries by position, which just shifted.
+        self.library_rename = None;
+        self.library_delete = None;
+        self.history.add(entry);
+        self.save_history();
+    }
+
+    fn save_history(&mut self) {
+        if let Err(err) = save_history(&self.history) {
+            self.last_error = Some(format!("Failed to save the recording library: {err}"));
+        }
+    }
+
+    pub(super) fn select_section(&mut self, section: Section) {
+        if section == Section::Library && self.current_section != Section::Library {
+            self.history.refresh();
+        }
+        self.current_section = section;
+    }
+
+    pub(super) fn open_recording(&mut self, index: usize, containing_folder: bool) {
+        let Some(entry) = self.history.entries.get(index) else {
+            return;
+        };
+        let file = Path::new(&entry.file);
+        let target = match file.parent() {
+            Some(parent) if containing_folder => parent,
+            _ => file,
+        };
+        if let Err(err) = open_with_default_app(target) {
+            self.last_error = Some(err);
+        }
+    }
+
+    pub(super) fn show_recording_log(&mut self, index: usize) {
+        let Some(log_file) = self
+            .history
+            .entries
+            .get(index)
+            .and_then(|entry| entry.log_file.clone())
+        else {
+            return;
+        };
+        match fs::read(&log_file) {
+            Ok(bytes) => {
+                self.log_viewer = Some((log_file, String::from_utf8_lossy(&bytes).into_owned()));
+            }
+            Err(err) => {
+                self.last_error = Some(format!(
+                    "Cannot open {log_file} (logs are pruned after 30 days or 100 sessions): {err}"
+                ));
+            }
+        }
+    }
+
+    pub(super) fn open_logs_folder(&mut self) {
+        let result = match logs_dir() {
+            Some(dir) => fs::create_dir_all(&dir)
+                .map_err(|err| format!("Failed to create {}: {err}", dir.display()))
+                .and_then(|()| open_with_default_app(&dir)),
+            None => Err("Cannot locate a state directory (HOME is not set).".to_string()),
+        };
+        if let Err(err) = result {
+            self.last_error = Some(err);
+        }
+    }
+
+    pub(super) fn rename_recording(&mut self, index: usize, new_name: &str) -> bool {
+        let result = self.history.rename(index, new_name);
+        self.save_history();
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                self.last_error = Some(err);
+                false
+            }
+        }
+    }
+
+    pub(super) fn delete_recording(&mut self, index: usize) {
+        if let Err(err) = self.history.delete(index) {
+            self.last_error = Some(err);
+        }
+        self.save_history();
+    }
+
+    pub(super) fn forget_recording(&mut self, index: usize) {
+        self.history.forget(index);
+        self.save_history();
+    }
+
+    pub(super) fn open_trim_dialog(&mut self, index: usize) {
+        let Some(entry) = self.history.entries.get(index) else {
+            return;
+        };
+        if self
+            .trim_dialog
+            .as_ref()
+            .is_some_and(|dialog| dialog.running)
+        {
+            self.last_error = Some("Wait for the current trim to finish.".to_string());
+            return;
+        }
+        let mut dialog = TrimDialog::new(entry.file.clone(), entry.duration_secs);
+        if let Some(previous) = self.trim_dialog.take() {
+            self.close_trim_dialog(previous);
+        }
+        dialog.request_thumbnail(TrimEdge::Start);
+        self.trim_dialog = Some(dialog);
+    }
+
+    pub(super) fn close_trim_dialog(&mut self, dialog: TrimDialog) {
+        if dialog.running {
+            // Let the ffmpeg job finish; its result is still recorded.
+            self.trim_dialog = Some(dialog);
+            return;
+        }
+        self.stale_thumbnails.extend(
+            dialog
+                .thumbnails
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .map(|thumbnail| thumbnail.uri),
+        );
+    }
+
+    pub(super) fn start_trim(&mut self) {
+        let Some(dialog) = &mut self.trim_dialog else {
+            return;
+        };
+        if dialog.running {
+            return;
+        }
+        dialog.output = derived_path(&dialog.source, "trimmed", None);
+        dialog.running = true;
+        dialog.error = None;
+        let (source, output) = (dialog.source.clone(), dialog.output.clone());
+        let (start, end, exact) = (dialog.start, dialog.end, dialog.exact);
+        let sender = dialog.sender.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(TrimUpdate::Finished(trim(
+                &source, &output, start, end, exact,
+            )));
+        });
+    }
+
+    fn poll_trim_dialog(&mut self) {
+        let Some(dialog) = &mut self.trim_dialog else {
+            return;
+        };
+        let updates: Vec<TrimUpdate> = dialog.receiver.try_iter().collect();
+        let mut finished = None;
+        for update in updates {
+            match update {
+                // Until ffprobe answers, the recording time stands in.
+                TrimUpdate::Duration(duration) => {
+                    if duration > 0.0 {
+                        dialog.end = duration;
+                    }
+                    dialog.duration = Some(dialog.end);
+                    dialog.request_thumbnail(TrimEdge::End);
+                }
+                TrimUpdate::Thumbnail(edge, at, result) => {
+                    let slot = edge as usize;
+                    if dialog.pending_thumbnails[slot] != Some(at) {
+                        continue;
+                    }
+                    dialog.pending_thumbnails[slot] = None;
+                    if let Some(Ok(thumbnail)) = dialog.thumbnails[slot].take() {
+                        self.stale_thumbnails.push(thumbnail.uri);
+                    }
+                    dialog.thumbnails[slot] = Some(result.map(|png| Thumbnail {
+                        uri: format!("bytes://trim-{slot}-{at:.3}.png"),
+                        png: png.into(),
+                    }));
+                }
+                TrimUpdate::Finished(result) => finished = Some(result),
+            }
+        }
+        match finished {
+            Some(Ok(mode)) => {
+                let Some(dialog) = self.trim_dialog.take() else {
+                    return;
+                };
+                let operation = format!(
+                    "trimmed {}–{} ({})",
+                    format_timestamp(dialog.start),
+                    format_timestamp(dialog.end),
+                    mode.label()
+                );
+                let source = self
+                    .history
+                    .entries
+                    .iter()
+                    .find(|entry| entry.file == dialog.source)
+                    .cloned()
+                    .unwrap_or_else(|| RecordingEntry {
+                        file: dialog.source.clone(),
+                        ..RecordingEntry::default()
+                    });
+                let mut entry = source.derive(&dialog.output, &operation);
+                entry.duration_secs = dialog.end - dialog.start;
+                self.add_recording(entry);
+                self.last_recording_summary =
+                    Some(format!("Saved to {}\n{operation}", dialog.output));
+                self.close_trim_dialog(dialog);
+            }
+            Some(Err(err)) => {
+                if let Some(dialog) = &mut self.trim_dialog {
+                    dialog.running = false;
+                    dialog.error = Some(err);
+                }
+            }
+            None => {}
+        }
+    }
+
+    pub(super) fn open_export_dialog(&mut self, index: usize) {
+        if self
+            .export_dialog
+            .as_ref()
+            .is_some_and(ExportDialog::running)
+        {
+            self.last_error = Some("Wait for the current export to finish.".to_string());
+            return;
+        }
+        let Some(entry) = self.history.entries.get(index) else {
+            return;
+        };
+        let preset = ExportPreset::TargetSize;
+        self.export_dialog = Some(ExportDialog {
+            output: export_output(&entry.file, preset),
+            source: entry.file.clone(),
+            preset,
+            target_mb: 10.0,
+            progress: None,
+            error: None,
+            duration: entry.duration_secs,
+            cancel: Arc::new(AtomicBool::new(false)),
+            receiver: None,
+        });
+    }
+
+    pub(super) fn start_export(&mut self) {
+        let Some(dialog) = &mut self.export_dialog else {
+            return;
+        };
+        if dialog.running() {
+            return;
+        }
+        dialog.output = export_output(&dialog.source, dialog.preset);
+        dialog.error = None;
+        dialog.progress = Some(0.0);
+        dialog.cancel.store(false, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel();
+        dialog.receiver = Some(receiver);
+        let (source, output) = (dialog.source.clone(), dialog.output.clone());
+        let (preset, target_mb, cancel) = (dialog.preset, dialog.target_mb, dialog.cancel.clone());
+        let estimate = dialog.duration;
+        std::thread::spawn(move || {
+            // The file's own duration sizes the bitrate better than the
+            // recording time does.
+            let report = probe(&source).ok();
+            let duration = report
+                .as_ref()
+                .and_then(|report| report.duration)
+                .unwrap_or(estimate);
+            let has_audio = report.is_none_or(|report| !report.audio_codecs.is_empty());
+            let result = plan_export(&source, &output, preset, target_mb, duration, has_audio)
+                .and_then(|plan| {
+                    run_export(&plan, duration, &cancel, |progress| {
+                        let _ = sender.send(ExportUpdate::Progress(progress));
+                    })
+                });
+            let _ = sender.send(ExportUpdate::Finished(result));
+        });
+    }
+
+    fn poll_export_dialog(&mut self) {
+        let Some(dialog) = &mut self.export_dialog else {
+            return;
+        };
+        let Some(receiver) = &dialog.receiver else {
+            return;
+        };
+        let mut finished = None;
+        for update in receiver.try_iter() {
+            match update {
+                ExportUpdate::Progress(progress) => dialog.progress = Some(progress),
+                ExportUpdate::Finished(result) => finished = Some(result),
             }
+        }
+        let Some(result) = finished else {
+            return;
+        };
+        dialog.receiver = None;
+        dialog.progress = None;
+        let size = match result {
+            Ok(size) => size,
             Err(err) => {
-                self.last_error = Some(format!("Failed to start wf-recorder: {err}"));
+                dialog.error = Some(err);
+                return;
             }
+        };
+        let Some(dialog) = self.export_dialog.take() else {
+            return;
+        };
+        let operation = match dialog.preset {
+            ExportPreset::TargetSize => format!("compressed to fit {} MB", dialog.target_mb),
+            preset => format!("exported as {}", preset.label()),
+        };
+        let mut summary = format!(
+            "Saved to {} ({})\n{operation}",
+            dialog.output,
+            format_file_size(size)
+        );
+        if dialog.preset == ExportPreset::TargetSize && size as f64 > dialog.target_mb * 1_000_000.0
+        {
+            summary
+                .push_str("\n⚠ The file is over the size cap; export again with a lower target.");
         }
+        let source = self
+            .history
+            .entries
+            .iter()
+            .find(|entry| entry.file == dialog.source)
+            .cloned()
+            .unwrap_or_else(|| RecordingEntry {
+                file: dialog.source.clone(),
+                ..RecordingEntry::default()
+            });
+        self.add_recording(source.derive(&dialog.output, &operation));
+        self.last_recording_summary = Some(summary);
     }
 
-    pub(super) fn stop_recording(&mut self) {
-        let mut status = RecorderStatus::Idle;
-        std::mem::swap(&mut status, &mut self.status);
-        if let RecorderStatus::Running(mut process) = status {
-            let pid = process.child.id();
-            match request_graceful_stop(pid) {
-                Ok(()) => {
-                    self.status = RecorderStatus::Running(process);
-                }
-                Err(err) => {
-                    process.finish();
-                    self.status = RecorderStatus::Idle;
-                    self.last_error = Some(format!("Failed to signal wf-recorder: {err}"));
-                }
-            }
-        } else {
-            self.status = status;
+    pub(super) fn start_recovery(&mut self, file: String) {
+        let file = file.trim().to_string();
+        if file.is_empty() || self.recovery_receiver.is_some() {
+            return;
         }
+        let (tx, rx) = mpsc::channel();
+        self.recovery_result = None;
+        self.recovery_receiver = Some(rx);
+        std::thread::spawn(move || {
+            let result = recover(&file);
+            let _ = tx.send((file, result));
+        });
     }
 
-    pub(super) fn force_stop_recording(&mut self) {
-        let mut status = RecorderStatus::Idle;
-        std::mem::swap(&mut status, &mut self.status);
-        if let RecorderStatus::Running(mut process) = status {
-            if let Err(err) = process.child.kill() {
-                self.last_error = Some(format!("Failed to terminate wf-recorder: {err}"));
-            }
-            process.finish();
+    // The salvaged copy joins the library next to its source.
+    fn finish_recovery(&mut self, source: &str, recovery: Recovery) {
+        if let Some(recovered) = &recovery.recovered {
+            let entry = self
+                .history
+                .entries
+                .iter()
+                .find(|entry| entry.file == source)
+                .cloned()
+                .unwrap_or_else(|| RecordingEntry {
+                    file: source.to_string(),
+                    recorded_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    ..RecordingEntry::default()
+                })
+                .derive(recovered, "recovered");
+            self.add_recording(entry);
         }
-        self.status = RecorderStatus::Idle;
+        self.recovery_result = Some(Ok(recovery.message));
     }
 
-    pub(super) fn poll_process(&mut self) {
-        let current_status = std::mem::replace(&mut self.status, RecorderStatus::Idle);
-        self.status = match current_status {
-            RecorderStatus::Running(mut process) => match process.child.try_wait() {
-                Ok(Some(status)) => {
-                    process.finish();
-                    let duration = process.started_at.elapsed();
-                    let file_exists = Path::new(&process.output_file).exists();
-                    let exit_suffix = format_exit_status(status);
-                    let summary = if file_exists {
-                        format!(
-                            "Saved to {}\nwf-recorder exited after {:.1} seconds{}",
-                            process.output_file,
-                            duration.as_secs_f32(),
-                            exit_suffix
-                        )
-                    } else {
-                        let mut message = format!(
-                            "wf-recorder exited after {:.1} seconds{} but no file was created at {}.",
-                            duration.as_secs_f32(),
-                            exit_suffix,
-                            process.output_file
-                        );
-                        if let Some(log_tail) = self.recent_log_tail(8) {
-                            message.push_str("\nRecent wf-recorder output:\n");
-                            message.push_str(&log_tail);
-                        } else {
-                            message.push_str(
-                                "\nNo wf-recorder output was captured. Use Tools & Diagnostics → wf-recorder log for details.",
-                            );
-                        }
-                        message
-                    };
-                    if !file_exists {
-                        self.last_error.get_or_insert_with(|| {
-                            "Recording did not produce an output file.".to_string()
-                        });
-                    }
-                    self.last_recording_summary = Some(summary);
-                    RecorderStatus::Idle
-                }
-                Ok(None) => RecorderStatus::Running(process),
-                Err(err) => {
-                    self.last_error = Some(format!("Failed to poll wf-recorder status: {err}"));
-                    RecorderStatus::Running(process)
-                }
-            },
-            status => status,
-        };
+    pub(super) fn adopt_stray_recorder(&mut self, index: usize) -> bool {
+        if self.status.is_active() {
+            self.last_error =
+                Some("Stop the current recording before adopting another one.".to_string());
+            return false;
+        }
+        if index >= self.stray_recorders.len() {
+            return false;
+        }
+        let record = self.stray_recorders.remove(index);
+        forget_recorder(&record);
+        if !is_recorder_process(record.pid) {
+            self.last_error = Some(format!(
+                "wf-recorder (pid {}) has already exited.",
+                record.pid
+            ));
+            return false;
+        }
+
+        let pid_file = RecorderPidFile::create(&RecorderPidRecord {
+            owner_pid: std::process::id(),
+            ..record.clone()
+        })
+        .ok();
+        self.status = RecorderStatus::Running(RecorderProcess {
+            handle: RecorderHandle::Adopted(record.pid),
+            stdout_join: None,
+            stderr_join: None,
+            started_at: Instant::now()
+                .checked_sub(record.running_for())
+                .unwrap_or_else(Instant::now),
+            segment_file: record.output_file.clone(),
+            pid_file,
+            session: RecordingSession::new(
+                Vec::new(),
+                record.output_file,
+                SessionLock::acquire().ok(),
+            ),
+        });
+        self.last_error = None;
+        self.last_recording_summary = None;
+        true
+    }
+
+    pub(super) fn stop_stray_recorder(&mut self, index: usize) {
+        if self.adopt_stray_recorder(index) {
+            self.stop_recording();
+        }
+    }
+
+    pub(super) fn kill_stray_recorder(&mut self, index: usize) {
+        if index >= self.stray_recorders.len() {
+            return;
+        }
+        let record = self.stray_recorders.remove(index);
+        if is_recorder_process(record.pid)
+            && let Err(err) = send_stop_signal(record.pid, StopStage::Kill)
+        {
+            self.last_error = Some(format!(
+                "Failed to kill wf-recorder (pid {}): {err}",
+                record.pid
+            ));
+        }
+        forget_recorder(&record);
     }
 
     pub(super) fn invoke_simple_action(&mut self, action: SimpleAction) {
@@ -311,6 +1507,45 @@ impl RecorderApp {
         });
     }
 
+    // The cached listing is shown meanwhile; ffmpeg may have been upgraded.
+    pub(super) fn request_ffmpeg_capabilities_refresh(&mut self) {
+        if self.ffmpeg_capabilities_loading {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.ffmpeg_capabilities_loading = true;
+        self.ffmpeg_capabilities_error = None;
+        self.ffmpeg_capabilities_receiver = Some(rx);
+        std::thread::spawn(move || {
+            let result = detect_ffmpeg_capabilities();
+            if let Ok(capabilities) = &result {
+                let _ = save_cached_capabilities(capabilities);
+            }
+            let _ = tx.send(result);
+        });
+    }
+
+    // Only reads a few sysfs entries, so it runs on the UI thread.
+    pub(super) fn refresh_render_nodes(&mut self) {
+        self.render_nodes = detect_render_nodes(Path::new("/"));
+    }
+
+    // Fills in whatever the selected VAAPI codec still lacks.
+    pub(super) fn apply_vaapi_setup(&mut self) {
+        let Some(setup) = suggest_vaapi_setup(&self.config, &self.render_nodes) else {
+            return;
+        };
+        if let Some(device) = setup.device {
+            self.config.encoding_device = device;
+        }
+        if let Some(filter) = setup.filter {
+            self.config.filter = filter;
+        }
+        if let Some(pixel_format) = setup.pixel_format {
+            self.config.pixel_format = pixel_format;
+        }
+    }
+
     pub(super) fn request_window_refresh(&mut self) {
         if self.windows_loading {
             return;
@@ -340,6 +1575,7 @@ impl RecorderApp {
     }
 
     pub(super) fn poll_async_tasks(&mut self) {
+        #[allow(clippy::collapsible_if)]
         if self.outputs_loading {
             if let Some(receiver) = &self.outputs_receiver {
                 match receiver.try_recv() {
@@ -365,6 +1601,31 @@ impl RecorderApp {
             }
         }
 
+        if self.ffmpeg_capabilities_loading
+            && let Some(receiver) = &self.ffmpeg_capabilities_receiver
+        {
+            match receiver.try_recv() {
+                Ok(Ok(capabilities)) => {
+                    self.ffmpeg_capabilities = capabilities;
+                    self.ffmpeg_capabilities_loading = false;
+                    self.ffmpeg_capabilities_receiver = None;
+                }
+                Ok(Err(err)) => {
+                    self.ffmpeg_capabilities_error = Some(err);
+                    self.ffmpeg_capabilities_loading = false;
+                    self.ffmpeg_capabilities_receiver = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.ffmpeg_capabilities_loading = false;
+                    self.ffmpeg_capabilities_receiver = None;
+                    self.ffmpeg_capabilities_error =
+                        Some("ffmpeg discovery task disconnected unexpectedly.".to_string());
+                }
+            }
+        }
+
+        #[allow(clippy::collapsible_if)]
         if self.windows_loading {
             if let Some(receiver) = &self.windows_receiver {
                 match receiver.try_recv() {
@@ -390,6 +1651,7 @@ impl RecorderApp {
             }
         }
 
+        #[allow(clippy::collapsible_if)]
         if self.audio_devices_loading {
             if let Some(receiver) = &self.audio_devices_receiver {
                 match receiver.try_recv() {
@@ -415,6 +1677,82 @@ impl RecorderApp {
             }
         }
 
+        if let Some(receiver) = &self.concat_receiver {
+            match receiver.try_recv() {
+                Ok(Ok((summary, file))) => {
+                    self.last_recording_summary = Some(summary);
+                    self.concat_receiver = None;
+                    if let Some(mut record) = self.pending_history.take() {
+                        record.set_file(&file);
+                        self.record_history(record);
+                    }
+                }
+                Ok(Err(err)) => {
+                    self.last_error = Some(err);
+                    self.concat_receiver = None;
+                    self.pending_history = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.concat_receiver = None;
+                    self.last_error =
+                        Some("Segment joining task disconnected unexpectedly.".to_string());
+                }
+            }
+        }
+
+        if let Some(receiver) = &self.recovery_receiver {
+            match receiver.try_recv() {
+                Ok((source, result)) => {
+                    self.recovery_receiver = None;
+                    match result {
+                        Ok(recovery) => self.finish_recovery(&source, recovery),
+                        Err(err) => self.recovery_result = Some(Err(err)),
+                    }
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.recovery_receiver = None;
+                    self.recovery_result =
+                        Some(Err("Recovery task disconnected unexpectedly.".to_string()));
+                }
+            }
+        }
+
+        self.poll_trim_dialog();
+        self.poll_export_dialog();
+
+        let verified: Vec<(String, Vec<String>)> = self.verify_receiver.try_iter().collect();
+        for (file, warnings) in verified {
+            self.verify_pending = self.verify_pending.saturating_sub(1);
+            if warnings.is_empty() {
+                continue;
+            }
+            let summary = self.last_recording_summary.get_or_insert_with(String::new);
+            if !summary.is_empty() {
+                summary.push('\n');
+            }
+            summary.push_str(&format!("Integrity check of {file}:"));
+            for warning in &warnings {
+                summary.push_str("\n  ⚠ ");
+                summary.push_str(warning);
+            }
+            if self.history.set_warnings(&file, warnings) {
+                self.save_history();
+            }
+        }
+
+        let control_messages: Vec<ControlMessage> = self
+            .control_receiver
+            .as_ref()
+            .map(|receiver| receiver.try_iter().collect())
+            .unwrap_or_default();
+        for message in control_messages {
+            let response = self.handle_control_request(message.request);
+            let _ = message.reply.send(response);
+        }
+
+        #[allow(clippy::collapsible_if)]
         if self.log_dirty.swap(false, Ordering::Relaxed) {
             if let Ok(buffer) = self.log_buffer.lock() {
                 self.log_display = buffer.clone();
@@ -451,15 +1789,380 @@ impl RecorderApp {
         }
     }
 
-    pub(super) fn build_command_preview(&self) -> Result<String, String> {
-        let preview_timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+    fn build_command_preview(&self) -> Result<String, String> {
         let (args, _) = self.config.build_command_args(
-            Some(preview_timestamp),
-            self.detect_screen_geometry_override(),
+            &TemplateContext::now(&self.profiles.active),
+            detect_screen_geometry_override(&self.config),
         )?;
         Ok(shell_preview(args))
     }
 
+    fn handle_control_request(&mut self, request: ControlRequest) -> ControlResponse {
+        let response = match request {
+            ControlRequest::Start => self.control_start(),
+            ControlRequest::Record {
+                profile,
+                mode,
+                geometry,
+            } => {
+                if self.status.is_active() {
+                    ControlResponse::error("A recording is already running.")
+                } else if let Some(profile) = &profile
+                    && self.profiles.get(profile).is_none()
+                {
+                    ControlResponse::error(format!("No profile named \"{profile}\"."))
+                } else {
+                    if let Some(profile) = profile {
+                        self.switch_profile(&profile);
+                    }
+                    if let Some(mode) = mode {
+                        self.apply_capture_mode(mode, geometry);
+                    } else if let Some(geometry) = geometry {
+                        self.apply_capture_mode(CaptureMode::Area, Some(geometry));
+                    }
+                    self.control_start()
+                }
+            }
+            ControlRequest::Focus => {
+                self.focus_requested = true;
+                ControlResponse::ok("Focused the running wf-recorder UI.")
+            }
+            ControlRequest::Stop | ControlRequest::ForceStop if !self.status.is_active() => {
+                ControlResponse::error("No recording is running.")
+            }
+            ControlRequest::Stop | ControlRequest::ForceStop
+                if matches!(self.status, RecorderStatus::Scheduled(_)) =>
+            {
+                self.stop_recording();
+                ControlResponse::ok("Scheduled recording cancelled.")
+            }
+            ControlRequest::Stop => {
+                let finalizing = matches!(self.status, RecorderStatus::Stopping(..));
+                self.stop_recording();
+                if finalizing {
+                    ControlResponse::ok("wf-recorder is already finalizing the file.")
+                } else {
+                    ControlResponse::ok("Stop requested; wf-recorder is finalizing the file.")
+                }
+            }
+            ControlRequest::ForceStop => {
+                self.force_stop_recording();
+                ControlResponse::ok("wf-recorder was killed.")
+            }
+            ControlRequest::Pause => match &self.status {
+                RecorderStatus::Running(process) if process.session.can_pause() => {
+                    self.pause_recording();
+                    ControlResponse::ok("Pausing; the current segment is being finalized.")
+                }
+                RecorderStatus::Running(_) => {
+                    ControlResponse::error("This recording cannot be paused.")
+                }
+                RecorderStatus::Paused(_) => {
+                    ControlResponse::ok("The recording is already paused.")
+                }
+                _ => ControlResponse::error("No recording is running."),
+            },
+            ControlRequest::Resume => {
+                if !matches!(self.status, RecorderStatus::Paused(_)) {
+                    ControlResponse::error("The recording is not paused.")
+                } else {
+                    self.resume_recording();
+                    match (&self.status, &self.last_error) {
+                        (RecorderStatus::Running(_), _) => {
+                            ControlResponse::ok("Recording resumed.")
+                        }
+                        (_, Some(err)) => ControlResponse::error(err.clone()),
+                        _ => ControlResponse::error("wf-recorder did not start."),
+                    }
+                }
+            }
+            ControlRequest::Status => ControlResponse::ok(self.status_summary()),
+            ControlRequest::SwitchProfile { profile } => {
+                if self.status.is_active() {
+                    ControlResponse::error("Stop the recording before switching profiles.")
+                } else if self.profiles.get(&profile).is_none() {
+                    ControlResponse::error(format!("No profile named \"{profile}\"."))
+                } else {
+                    self.switch_profile(&profile);
+                    ControlResponse::ok(format!("Switched to profile \"{profile}\"."))
+                }
+            }
+            ControlRequest::SetCaptureMode { mode, geometry } => {
+                if self.status.is_active() {
+                    ControlResponse::error("Stop the recording before changing the capture mode.")
+                } else {
+                    self.apply_capture_mode(mode, geometry);
+                    ControlResponse::ok("Capture mode updated.")
+                }
+            }
+        };
+        response.with_status(self.control_status())
+    }
+
+    fn control_start(&mut self) -> ControlResponse {
+        if self.status.is_active() {
+            return ControlResponse::error("A recording is already running.");
+        }
+        // Only an error from this attempt explains why it did not start.
+        self.last_error = None;
+        self.start_recording();
+        match (&self.status, &self.collision_prompt, &self.last_error) {
+            (RecorderStatus::Running(_), _, _) => ControlResponse::ok("Recording started."),
+            (RecorderStatus::Scheduled(pending), _, _) => ControlResponse::ok(format!(
+                "Recording starts in {}.",
+                format_duration(pending.remaining_rounded())
+            )),
+            (_, Some(file), _) => ControlResponse::error(format!(
+                "{file} already exists; choose overwrite, suffix or cancel in the UI."
+            )),
+            (_, None, Some(err)) => ControlResponse::error(err.clone()),
+            (_, None, None) => ControlResponse::error("wf-recorder did not start."),
+        }
+    }
+
+    fn apply_capture_mode(&mut self, mode: CaptureMode, geometry: Option<String>) {
+        self.config.capture_mode = mode;
+        if let Some(geometry) = geometry {
+            match mode {
+                CaptureMode::Area => self.config.area_geometry = geometry,
+                CaptureMode::Window => {
+                    // Keep the id in step so the UI does not show the window
+                    // that was selected before.
+                    let window = self
+                        .available_windows
+                        .iter()
+                        .find(|window| window.geometry == geometry)
+                        .cloned()
+                        .unwrap_or_default();
+                    self.config.selected_window_id = window.id;
+                    self.config.selected_window_app = window.app;
+                    self.config.selected_window_title = window.title;
+                    self.config.selected_window_geometry = geometry;
+                }
+                CaptureMode::Screen => {}
+            }
+        }
+        if mode == CaptureMode::Window {
+            self.request_window_refresh();
+        }
+    }
+
+    fn control_status(&self) -> ControlStatus {
+        let (state, output_file, elapsed_secs) = match &self.status {
+            RecorderStatus::Idle => ("idle", None, None),
+            RecorderStatus::Scheduled(_) => ("scheduled", None, None),
+            RecorderStatus::Running(process) => (
+                "recording",
+                Some(process.output_file().to_string()),
+                Some(process.elapsed().as_secs_f64()),
+            ),
+            RecorderStatus::Stopping(process, _) => (
+                "stopping",
+                Some(process.output_file().to_string()),
+                Some(process.elapsed().as_secs_f64()),
+            ),
+            RecorderStatus::Paused(session) => (
+                if session.restart_at.is_some() {
+                    "restarting"
+                } else {
+                    "paused"
+                },
+                Some(session.output_file.clone()),
+                Some(session.recorded.as_secs_f64()),
+            ),
+        };
+        ControlStatus {
+            state: state.to_string(),
+            profile: self.profiles.active.clone(),
+            capture_mode: Some(self.config.capture_mode),
+            output_file,
+            elapsed_secs,
+        }
+    }
+
+    fn status_summary(&self) -> String {
+        match &self.status {
+            RecorderStatus::Idle => "Idle.".to_string(),
+            RecorderStatus::Scheduled(pending) => match &pending.scheduled_for {
+                Some(time) => format!(
+                    "Recording starts at {time} (in {}).",
+                    format_duration(pending.remaining_rounded())
+                ),
+                None => format!(
+                    "Recording starts in {}.",
+                    format_duration(pending.remaining_rounded())
+                ),
+            },
+            RecorderStatus::Running(process) => format!(
+                "Recording to {} for {:.1}s.",
+                process.output_file(),
+                process.elapsed().as_secs_f32()
+            ),
+            RecorderStatus::Stopping(process, progress) => format!(
+                "Finalizing {} after {}.",
+                process.output_file(),
+                progress.stage.signal_name()
+            ),
+            RecorderStatus::Paused(session) if session.restart_at.is_some() => format!(
+                "Restarting wf-recorder for {} (attempt {}).",
+                session.output_file, session.restarts
+            ),
+            RecorderStatus::Paused(session) => format!(
+                "Paused {} after {:.1}s in {} segments.",
+                session.output_file,
+                session.recorded.as_secs_f32(),
+                session.parts.len()
+            ),
+        }
+    }
+
+    pub(super) fn switch_profile(&mut self, name: &str) {
+        if self.profiles.active == name {
+            return;
+        }
+        self.profiles.store_active_config(&self.config);
+        match self.profiles.set_active(name) {
+            Ok(config) => {
+                self.config = config;
+                self.last_error = None;
+                self.request_output_refresh();
+                if self.config.capture_mode == CaptureMode::Window {
+                    self.request_window_refresh();
+                }
+            }
+            Err(err) => self.last_error = Some(err),
+        }
+    }
+
+    pub(super) fn open_profile_dialog(&mut self, kind: ProfileDialogKind) {
+        let name = match kind {
+            ProfileDialogKind::Create => self.profiles.unique_name("New profile"),
+            ProfileDialogKind::Duplicate => self
+                .profiles
+                .unique_name(&format!("{} copy", self.profiles.active)),
+            ProfileDialogKind::Rename => self.profiles.active.clone(),
+        };
+        self.profile_dialog = Some(ProfileDialog {
+            kind,
+            name,
+            error: None,
+        });
+    }
+
+    pub(super) fn confirm_profile_dialog(&mut self) {
+        let Some(dialog) = self.profile_dialog.as_mut() else {
+            return;
+        };
+        // Create and Duplicate switch to the new profile, which would swap the
+        // config of the running recording.
+        if dialog.kind != ProfileDialogKind::Rename && self.status.is_active() {
+            dialog.error = Some("Stop the recording before switching profiles.".to_string());
+            return;
+        }
+        let name = dialog.name.trim().to_string();
+        self.profiles.store_active_config(&self.config);
+        let active = self.profiles.active.clone();
+        let result = match dialog.kind {
+            ProfileDialogKind::Create => self.profiles.create(&name, RecorderConfig::default()),
+            ProfileDialogKind::Duplicate => self.profiles.duplicate(&active, &name),
+            ProfileDialogKind::Rename => self.profiles.rename(&active, &name),
+        };
+        match result {
+            Ok(()) => {
+                let kind = dialog.kind;
+                self.profile_dialog = None;
+                if kind != ProfileDialogKind::Rename {
+                    self.switch_profile(&name);
+                }
+            }
+            Err(err) => dialog.error = Some(err),
+        }
+    }
+
+    pub(super) fn delete_active_profile(&mut self) {
+        let active = self.profiles.active.clone();
+        match self.profiles.delete(&active) {
+            Ok(()) => {
+                self.config = self.profiles.active_config();
+                self.last_error = None;
+            }
+            Err(err) => self.last_error = Some(err),
+        }
+    }
+
+    pub(super) fn output_preview(&mut self) -> &OutputPreview {
+        let preview = match self.output_preview.take() {
+            Some(preview)
+                if preview.config == self.config
+                    && preview.profile == self.profiles.active
+                    && preview.resolved_at.elapsed() < OUTPUT_PREVIEW_REFRESH =>
+            {
+                preview
+            }
+            _ => OutputPreview {
+                config: self.config.clone(),
+                profile: self.profiles.active.clone(),
+                resolved_at: Instant::now(),
+                file: self.config.preview_output_file(&self.profiles.active),
+                command: self.build_command_preview(),
+            },
+        };
+        self.output_preview.insert(preview)
+    }
+
+    pub(super) fn current_settings(&self) -> Settings {
+        let mut profiles = self.profiles.clone();
+        profiles.store_active_config(&self.config);
+        Settings {
+            profiles,
+            ui: UiSettings {
+                dark_theme: self.dark_theme,
+                sidebar_state: self.sidebar_state,
+                allow_concurrent_sessions: self.allow_concurrent_sessions,
+            },
+            ..Settings::default()
+        }
+    }
+
+    pub(super) fn persist_settings_if_changed(&mut self) {
+        let current = self.current_settings();
+        if current == self.saved_settings {
+            self.settings_changed_at = None;
+            return;
+        }
+        let changed_at = *self.settings_changed_at.get_or_insert_with(Instant::now);
+        if changed_at.elapsed() >= SETTINGS_SAVE_DELAY {
+            self.write_settings(current);
+        }
+    }
+
+    pub(super) fn flush_settings(&mut self) {
+        let current = self.current_settings();
+        if current != self.saved_settings {
+            self.write_settings(current);
+        }
+    }
+
+    fn write_settings(&mut self, settings: Settings) {
+        if let Err(err) = save_settings(&settings) {
+            self.last_error = Some(err);
+        }
+        self.saved_settings = settings;
+        self.settings_changed_at = None;
+    }
+
+    fn log_note(&self, session_log: Option<&SessionLog>, line: String) {
+        if let Some(log) = session_log {
+            log.write(LogSource::Supervisor, &line);
+        }
+        let entry = LogEntry {
+            source: LogSource::Supervisor,
+            line,
+        };
+        push_log_entry(&self.log_entries, &self.log_buffer, entry);
+        self.log_dirty.store(true, Ordering::Relaxed);
+    }
+
     fn recent_log_tail(&self, lines: usize) -> Option<String> {
         let buffer = self.log_buffer.lock().ok()?;
         if buffer.is_empty() {
@@ -469,23 +2172,137 @@ impl RecorderApp {
         collected.reverse();
         Some(collected.join("\n"))
     }
+}
 
-    fn detect_screen_geometry_override(&self) -> Option<String> {
-        if self.config.capture_mode != CaptureMode::Screen {
-            return None;
-        }
-        let output = self.config.output.trim();
-        if output.is_empty() {
-            return None;
+impl Default for RecorderApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn session_record(session: &RecordingSession) -> FinishedRecording {
+    finished_recording(
+        session,
+        &session.output_file,
+        session.started,
+        session.recorded,
+    )
+}
+
+fn finished_recording(
+    session: &RecordingSession,
+    file: &str,
+    started: chrono::DateTime<Local>,
+    duration: Duration,
+) -> FinishedRecording {
+    let mut entry = RecordingEntry::new(
+        file,
+        started,
+        duration,
+        session.details.clone(),
+        &session.last_exit,
+    );
+    entry.log_file = session
+        .log
+        .as_ref()
+        .map(|log| log.path().to_string_lossy().into_owned());
+    let config = session.config.as_ref();
+    FinishedRecording {
+        expectations: config
+            .filter(|config| config.verify_recordings)
+            .map(|config| Expectations::from_config(config, duration)),
+        entry,
+        metadata: config.filter(|config| config.write_metadata).map(|config| {
+            RecordingMetadata::new(
+                file,
+                &session.args,
+                config,
+                &session.details.profile,
+                started,
+                duration,
+                &session.last_exit,
+            )
+        }),
+    }
+}
+
+// Joins `parts` into `output_file` and, for MKV-first recordings, remuxes that
+// into `remux_to`. Returns the summary and the file holding the recording.
+fn finalize_parts(
+    parts: &[String],
+    output_file: &str,
+    remux_to: Option<&str>,
+    summary: String,
+) -> Result<(String, String), String> {
+    match parts {
+        [only] => fs::rename(only, output_file)
+            .map_err(|err| format!("Failed to move {only} to {output_file}: {err}"))?,
+        parts => concat_segments(parts, output_file)
+            .map_err(|err| format!("{err}\nThe segments were kept: {}", parts.join(", ")))?,
+    }
+    let Some(target) = remux_to else {
+        return Ok((
+            format!("Saved to {output_file}\n{summary}"),
+            output_file.to_string(),
+        ));
+    };
+    match remux(output_file, target) {
+        Ok(()) => {
+            let _ = fs::remove_file(output_file);
+            Ok((format!("Saved to {target}\n{summary}"), target.to_string()))
         }
+        Err(err) => Ok((
+            format!("Saved to {output_file}\n{summary}\n{err}; kept the Matroska recording."),
+            output_file.to_string(),
+        )),
+    }
+}
 
-        detect_output_geometry(output).ok().flatten()
+fn append_session_notes(
+    summary: &mut String,
+    session: &RecordingSession,
+    progress: Option<&StopProgress>,
+) {
+    if session.total_restarts > 0 {
+        summary.push_str(&format!(
+            "\nRestarted wf-recorder {} time(s) after unexpected exits.",
+            session.total_restarts
+        ));
+    }
+    if let Some(reason) = &session.auto_stop_reason {
+        summary.push('\n');
+        summary.push_str(reason);
+    }
+    if let Some(progress) = progress {
+        summary.push('\n');
+        summary.push_str(&progress.outcome());
     }
 }
 
-impl Default for RecorderApp {
-    fn default() -> Self {
-        Self::new()
+// Shared by the output readers and supervisor notes so both respect the cap.
+fn push_log_entry(
+    log_entries: &Mutex<Vec<LogEntry>>,
+    log_buffer: &Mutex<String>,
+    new_entry: LogEntry,
+) {
+    let mut snapshot: Option<Vec<LogEntry>> = None;
+    if let Ok(mut logs) = log_entries.lock() {
+        logs.push(new_entry.clone());
+        if logs.len() > 2048 {
+            let excess = logs.len() - 2048;
+            logs.drain(0..excess);
+            snapshot = Some(logs.clone());
+        }
+    }
+    if let Ok(mut text) = log_buffer.lock() {
+        if let Some(entries) = snapshot {
+            text.clear();
+            for entry in entries {
+                append_log_line(&mut text, &entry);
+            }
+        } else {
+            append_log_line(&mut text, &new_entry);
+        }
     }
 }
 
@@ -494,6 +2311,7 @@ fn spawn_reader<R: std::io::Read + Send + 'static>(
     log_entries: Arc<Mutex<Vec<LogEntry>>>,
     log_buffer: Arc<Mutex<String>>,
     log_dirty: Arc<AtomicBool>,
+    session_log: Option<Arc<SessionLog>>,
     source: LogSource,
 ) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || {
@@ -505,29 +2323,14 @@ fn spawn_reader<R: std::io::Read + Send + 'static>(
                 Ok(0) => break,
                 Ok(_) => {
                     let trimmed = line.trim_end_matches(['\n', '\r']);
+                    if let Some(log) = &session_log {
+                        log.write(source, trimmed);
+                    }
                     let new_entry = LogEntry {
                         source,
                         line: trimmed.to_string(),
                     };
-                    let mut snapshot: Option<Vec<LogEntry>> = None;
-                    if let Ok(mut logs) = log_entries.lock() {
-                        logs.push(new_entry.clone());
-                        if logs.len() > 2048 {
-                            let excess = logs.len() - 2048;
-                            logs.drain(0..excess);
-                            snapshot = Some(logs.clone());
-                        }
-                    }
-                    if let Ok(mut text) = log_buffer.lock() {
-                        if let Some(entries) = snapshot {
-                            text.clear();
-                            for entry in entries {
-                                append_log_line(&mut text, &entry);
-                            }
-                        } else {
-                            append_log_line(&mut text, &new_entry);
-                        }
-                    }
+                    push_log_entry(&log_entries, &log_buffer, new_entry);
                     log_dirty.store(true, Ordering::Relaxed);
                 }
                 Err(_) => break,
@@ -540,84 +2343,6 @@ fn append_log_line(buffer: &mut String, entry: &LogEntry) {
     if !buffer.is_empty() {
         buffer.push('\n');
     }
-    buffer.push_str(match entry.source {
-        LogSource::Stdout => "[stdout] ",
-        LogSource::Stderr => "[stderr] ",
-    });
+    buffer.push_str(entry.source.tag());
     buffer.push_str(&entry.line);
 }
-
-fn shell_preview(args: Vec<String>) -> String {
-    let mut preview = Vec::with_capacity(args.len() + 1);
-    preview.push("wf-recorder".to_string());
-    preview.extend(args.into_iter().map(shell_escape));
-    preview.join(" ")
-}
-
-fn shell_escape(arg: String) -> String {
-    if arg.is_empty() {
-        "''".to_string()
-    } else if arg
-        .chars()
-        .all(|c| c.is_ascii_alphanumeric() || "-_./:@".contains(c))
-    {
-        arg
-    } else {
-        format!("'{}'", arg.replace('\'', "'\\''"))
-    }
-}
-
-pub(super) fn format_exit_status(status: ExitStatus) -> String {
-    if let Some(code) = status.code() {
-        format!(", exit code {code}")
-    } else {
-        #[cfg(unix)]
-        {
-            if let Some(signal) = status.signal() {
-                return format!(
-                    ", terminated by signal {} ({})",
-                    signal,
-                    signal_name(signal)
-                );
-            }
-        }
-        #[cfg(not(unix))]
-        {
-            return ", terminated by signal".to_string();
-        }
-        #[cfg(unix)]
-        ", terminated by signal".to_string()
-    }
-}
-
-#[cfg(unix)]
-pub(super) fn request_graceful_stop(pid: u32) -> std::io::Result<()> {
-    let res = unsafe { libc::kill(pid as i32, libc::SIGINT) };
-    if res == 0 {
-        Ok(())
-    } else {
-        Err(std::io::Error::last_os_error())
-    }
-}
-
-#[cfg(not(unix))]
-pub(super) fn request_graceful_stop(_pid: u32) -> std::io::Result<()> {
-    Err(std::io::Error::new(
-        std::io::ErrorKind::Other,
-        "Graceful stop is only supported on Unix platforms",
-    ))
-}
-
-#[cfg(unix)]
-fn signal_name(signal: i32) -> &'static str {
-    match signal {
-        libc::SIGINT => "SIGINT",
-        libc::SIGTERM => "SIGTERM",
-        libc::SIGKILL => "SIGKILL",
-        libc::SIGHUP => "SIGHUP",
-        libc::SIGQUIT => "SIGQUIT",
-        libc::SIGABRT => "SIGABRT",
-        libc::SIGALRM => "SIGALRM",
-        _ => "unknown",
-    }
-}
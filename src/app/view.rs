@@ -1,14 +1,54 @@
 use eframe::egui::{self, Align2, Color32, Image, Key, RichText, Spinner, TextEdit};
 use eframe::{App, Frame};
+use std::path::Path;
 
 use crate::actions::SimpleAction;
-use crate::config::{AudioMode, CaptureMode, ParamEntry, RecorderConfig};
+use crate::capabilities::FfmpegEntry;
+use crate::codec_params::{ParamKind, ParamSpec, get_param, schema_for, set_param};
+use crate::compat::{
+    Severity, check_compatibility, first_error, muxer_for_extension, suggest_vaapi_setup,
+};
+use crate::config::{AudioMode, CaptureMode, CollisionPolicy, ParamEntry, RecorderConfig};
 use crate::constants::{
     COMMON_AUDIO_BACKENDS, COMMON_AUDIO_CODECS, COMMON_OUTPUT_FORMATS, COMMON_VIDEO_CODECS,
 };
-use crate::models::{AudioDevice, AudioDeviceKind, RecorderStatus};
+use crate::export::ExportPreset;
+use crate::history::format_file_size;
+use crate::models::{AudioDevice, AudioDeviceKind, RecorderStatus, RenderNode};
+use crate::quality::{QualityPreset, apply_quality_preset, current_settings, effective_settings};
+
+use crate::profiles::fields_differing_from_default;
+use crate::recovery::is_crash_resilient;
+use crate::schedule::format_duration;
+use crate::trim::format_timestamp;
+
+use super::state::{
+    ExportDialog, ProfileDialogKind, RecorderApp, Section, SidebarState, TrimDialog, TrimEdge,
+};
 
-use super::state::{RecorderApp, Section, SidebarState};
+#[derive(Clone, Copy)]
+enum StrayAction {
+    Adopt,
+    Stop,
+    Kill,
+    Ignore,
+}
+
+#[derive(Clone, Copy)]
+enum LibraryAction {
+    Open(usize),
+    OpenFolder(usize),
+    ShowLog(usize),
+    Trim(usize),
+    Export(usize),
+    StartRename(usize),
+    Rename(usize),
+    CancelRename,
+    ConfirmDelete(usize),
+    Delete(usize),
+    CancelDelete,
+    Forget(usize),
+}
 
 #[derive(Clone, Copy)]
 enum SidebarIcon {
@@ -16,6 +56,7 @@ enum SidebarIcon {
     Encoding,
     Audio,
     Tools,
+    Library,
 }
 
 fn sidebar_icon_source(icon: SidebarIcon, dark: bool) -> (&'static str, &'static [u8]) {
@@ -52,6 +93,14 @@ fn sidebar_icon_source(icon: SidebarIcon, dark: bool) -> (&'static str, &'static
             "bytes://tools_black.png",
             include_bytes!("../../assets/icons/png/tools_black.png"),
         ),
+        (SidebarIcon::Library, true) => (
+            "bytes://log_white.png",
+            include_bytes!("../../assets/icons/png/log_white.png"),
+        ),
+        (SidebarIcon::Library, false) => (
+            "bytes://log_black.png",
+            include_bytes!("../../assets/icons/png/log_black.png"),
+        ),
     }
 }
 
@@ -59,18 +108,51 @@ impl App for RecorderApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
         self.poll_process();
         self.poll_async_tasks();
+        apply_quality_preset(&mut self.config);
+        self.persist_settings_if_changed();
+        if self.settings_changed_at.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        }
 
         self.apply_theme(ctx);
 
-        if self.status.is_running() {
+        let close_requested = ctx.input(|input| input.viewport().close_requested());
+        if close_requested && matches!(self.status, RecorderStatus::Scheduled(_)) {
+            self.stop_recording();
+        }
+        if close_requested && self.status.is_active() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.close_dialog_open = true;
+        }
+        if self.quit_after_stop && !self.status.is_active() && self.concat_receiver.is_none() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+
+        if std::mem::take(&mut self.focus_requested) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+
+        if self.status.is_running()
+            || matches!(self.status, RecorderStatus::Scheduled(_))
+            || self.concat_receiver.is_some()
+            || self.verify_pending > 0
+            || self.recovery_receiver.is_some()
+            || self.trim_dialog.as_ref().is_some_and(TrimDialog::busy)
+            || self
+                .export_dialog
+                .as_ref()
+                .is_some_and(ExportDialog::running)
+        {
             ctx.request_repaint_after(std::time::Duration::from_millis(200));
         }
         egui_extras::install_image_loaders(ctx);
-        if self.outputs_loading {
+        if self.outputs_loading || self.ffmpeg_capabilities_loading {
             ctx.request_repaint_after(std::time::Duration::from_millis(300));
         }
 
-        if self.status.is_running() {
+        if self.status.is_active() {
             let ctrl_c_pressed = ctx.input(|input| {
                 input.key_pressed(Key::C) && (input.modifiers.command || input.modifiers.ctrl)
             });
@@ -98,8 +180,17 @@ impl App for RecorderApp {
                     {
                         self.apply_theme(ctx);
                     }
+                    ui.separator();
+                    self.profile_controls(ui);
                 });
             });
+        self.profile_dialog_window(ctx);
+        self.close_dialog_window(ctx);
+        self.collision_dialog_window(ctx);
+        self.log_viewer_window(ctx);
+        self.trim_dialog_window(ctx);
+        self.export_dialog_window(ctx);
+        self.stray_recorders_window(ctx);
 
         match self.sidebar_state {
             SidebarState::Expanded => {
@@ -137,6 +228,7 @@ impl App for RecorderApp {
                                 SidebarIcon::Tools,
                                 "Tools & Diagnostics",
                             ),
+                            (Section::Library, SidebarIcon::Library, "Recordings"),
                         ];
 
                         ui.style_mut().visuals.widgets.inactive.rounding =
@@ -167,7 +259,7 @@ impl App for RecorderApp {
                                 button = button.fill(egui::Color32::from_rgb(50, 100, 200));
                             }
                             if ui.add_sized([ui.available_width(), 50.0], button).clicked() {
-                                self.current_section = section;
+                                self.select_section(section);
                             }
                             ui.add_space(5.0);
                         }
@@ -184,6 +276,7 @@ impl App for RecorderApp {
                             (Section::VideoEncoding, SidebarIcon::Encoding, "Encoding"),
                             (Section::AudioRecording, SidebarIcon::Audio, "Audio"),
                             (Section::ToolsDiagnostics, SidebarIcon::Tools, "Tools"),
+                            (Section::Library, SidebarIcon::Library, "Recordings"),
                         ] {
                             let selected = self.current_section == section;
                             let (uri, bytes) = sidebar_icon_source(icon_kind, self.dark_theme);
@@ -197,7 +290,7 @@ impl App for RecorderApp {
                                 button = button.fill(egui::Color32::from_rgb(50, 100, 200));
                             }
                             if ui.add(button).on_hover_text(label).clicked() {
-                                self.current_section = section;
+                                self.select_section(section);
                             }
                             ui.add_space(6.0);
                         }
@@ -210,6 +303,7 @@ impl App for RecorderApp {
             egui::Area::new("sidebar_reveal".into())
                 .anchor(Align2::LEFT_CENTER, [8.0, 0.0])
                 .show(ctx, |ui| {
+                    #[allow(clippy::needless_borrow)]
                     egui::Frame::window(&ui.style()).show(ui, |ui| {
                         if ui.button("▶").clicked() {
                             self.toggle_sidebar();
@@ -229,8 +323,20 @@ impl App for RecorderApp {
                 ui.separator();
             }
 
-            if let RecorderStatus::Running(process) = &self.status {
-                let elapsed = process.started_at.elapsed().as_secs_f32();
+            if let RecorderStatus::Scheduled(pending) = &self.status {
+                let message = match &pending.scheduled_for {
+                    Some(time) => format!(
+                        "Recording starts at {time} (in {}). Cancel with Stop.",
+                        format_duration(pending.remaining_rounded())
+                    ),
+                    None => format!(
+                        "Recording starts in {}… move this window out of frame.",
+                        format_duration(pending.remaining_rounded())
+                    ),
+                };
+                ui.colored_label(Color32::from_rgb(120, 210, 255), message);
+            } else if let RecorderStatus::Running(process) = &self.status {
+                let elapsed = process.elapsed().as_secs_f32();
                 ui.colored_label(
                     Color32::from_rgb(120, 210, 255),
                     format!(
@@ -238,21 +344,74 @@ impl App for RecorderApp {
                         elapsed
                     ),
                 );
+            } else if let RecorderStatus::Stopping(_, progress) = &self.status {
+                let message = match (progress.remaining(), progress.stage.next()) {
+                    (Some(remaining), Some(next)) => format!(
+                        "Finalizing… {} sent; escalating to {} in {:.0}s if wf-recorder does not exit.",
+                        progress.stage.signal_name(),
+                        next.signal_name(),
+                        remaining.as_secs_f32().ceil()
+                    ),
+                    _ => "Finalizing… SIGKILL sent; waiting for wf-recorder to exit.".to_string(),
+                };
+                ui.colored_label(Color32::from_rgb(255, 235, 140), message);
+            } else if let RecorderStatus::Paused(session) = &self.status
+                && let Some(restart_at) = session.restart_at
+            {
+                ui.colored_label(
+                    Color32::from_rgb(255, 235, 140),
+                    format!(
+                        "wf-recorder exited unexpectedly; restarting in {:.0}s (attempt {}). See the wf-recorder log for the exit status.",
+                        restart_at
+                            .saturating_duration_since(std::time::Instant::now())
+                            .as_secs_f32()
+                            .ceil(),
+                        session.restarts
+                    ),
+                );
+            } else if let RecorderStatus::Paused(session) = &self.status {
+                ui.colored_label(
+                    Color32::from_rgb(120, 210, 255),
+                    format!(
+                        "Paused after {:.1}s across {} segments. Resume to keep recording into the same file.",
+                        session.recorded.as_secs_f32(),
+                        session.parts.len()
+                    ),
+                );
             } else if let Some(summary) = &self.last_recording_summary {
                 ui.colored_label(Color32::LIGHT_GREEN, summary);
+                if self.concat_receiver.is_none()
+                    && self.history.entries.first().is_some_and(|entry| !entry.missing)
+                    && ui
+                        .small_button("Trim last recording…")
+                        .on_hover_text(self.history.entries[0].file.as_str())
+                        .clicked()
+                {
+                    self.open_trim_dialog(0);
+                }
             }
 
             egui::ScrollArea::vertical()
                 .auto_shrink([true, false])
                 .show(ui, |ui| {
                     let content_width = ui.available_width();
+                    // Editing a field the quality preset owns detaches it.
+                    let managed = current_settings(&self.config);
                     match self.current_section {
                         Section::CaptureBasics => self.general_section(ui, content_width),
                         Section::VideoEncoding => self.video_section(ui, content_width),
                         Section::AudioRecording => self.audio_section(ui, content_width),
                         Section::ToolsDiagnostics => self.advanced_section(ui, content_width),
+                        Section::Library => self.library_section(ui, content_width),
+                    }
+                    if self.config.quality_preset.is_some()
+                        && current_settings(&self.config) != managed
+                    {
+                        self.config.quality_preset = None;
+                    }
+                    if self.current_section != Section::Library {
+                        self.action_buttons(ui, content_width);
                     }
-                    self.action_buttons(ui, content_width);
                     if matches!(self.current_section, Section::ToolsDiagnostics) {
                         self.command_preview(ui, content_width);
                         self.log_view(ui, content_width);
@@ -260,6 +419,10 @@ impl App for RecorderApp {
                 });
         });
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.flush_settings();
+    }
 }
 
 impl RecorderApp {
@@ -271,6 +434,489 @@ impl RecorderApp {
         }
     }
 
+    fn profile_controls(&mut self, ui: &mut egui::Ui) {
+        let recording = self.status.is_active();
+        ui.label(RichText::new("Profile").strong());
+        let mut selected = self.profiles.active.clone();
+        ui.add_enabled_ui(!recording, |ui| {
+            egui::ComboBox::from_id_source("profile_combo")
+                .selected_text(&selected)
+                .show_ui(ui, |ui| {
+                    for name in self.profiles.names() {
+                        let label = if name == self.profiles.default_profile {
+                            format!("{name} (default)")
+                        } else {
+                            name.clone()
+                        };
+                        ui.selectable_value(&mut selected, name, label);
+                    }
+                });
+        })
+        .response
+        .on_disabled_hover_text("Stop the recording before switching profiles.");
+        if selected != self.profiles.active {
+            self.switch_profile(&selected);
+        }
+
+        ui.menu_button("Manage…", |ui| {
+            if ui
+                .add_enabled(!recording, egui::Button::new("New profile"))
+                .on_disabled_hover_text("Stop the recording before switching profiles.")
+                .clicked()
+            {
+                self.open_profile_dialog(ProfileDialogKind::Create);
+                ui.close_menu();
+            }
+            if ui
+                .add_enabled(!recording, egui::Button::new("Duplicate current"))
+                .on_disabled_hover_text("Stop the recording before switching profiles.")
+                .clicked()
+            {
+                self.open_profile_dialog(ProfileDialogKind::Duplicate);
+                ui.close_menu();
+            }
+            if ui.button("Rename current").clicked() {
+                self.open_profile_dialog(ProfileDialogKind::Rename);
+                ui.close_menu();
+            }
+            let is_default = self.profiles.active == self.profiles.default_profile;
+            if ui
+                .add_enabled(!is_default, egui::Button::new("Use as default on launch"))
+                .clicked()
+            {
+                let active = self.profiles.active.clone();
+                if let Err(err) = self.profiles.set_default(&active) {
+                    self.last_error = Some(err);
+                }
+                ui.close_menu();
+            }
+            if ui
+                .add_enabled(
+                    !recording && self.profiles.profiles.len() > 1,
+                    egui::Button::new("Delete current"),
+                )
+                .clicked()
+            {
+                self.delete_active_profile();
+                ui.close_menu();
+            }
+        });
+
+        let changed = fields_differing_from_default(&self.config);
+        if changed.is_empty() {
+            ui.weak("Matches defaults");
+        } else {
+            let summary = if changed.len() == 1 {
+                "1 setting differs from defaults".to_string()
+            } else {
+                format!("{} settings differ from defaults", changed.len())
+            };
+            ui.weak(summary)
+                .on_hover_text(format!("Changed: {}", changed.join(", ")));
+        }
+    }
+
+    fn profile_dialog_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = self.profile_dialog.as_mut() else {
+            return;
+        };
+        let title = match dialog.kind {
+            ProfileDialogKind::Create => "New profile",
+            ProfileDialogKind::Duplicate => "Duplicate profile",
+            ProfileDialogKind::Rename => "Rename profile",
+        };
+        let blocked = dialog.kind != ProfileDialogKind::Rename && self.status.is_active();
+        let mut confirm = false;
+        let mut cancel = false;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    TextEdit::singleline(&mut dialog.name)
+                        .hint_text("Profile name")
+                        .desired_width(260.0),
+                );
+                response.request_focus();
+                if response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+                    confirm = true;
+                }
+                if let Some(err) = &dialog.error {
+                    ui.colored_label(Color32::from_rgb(255, 120, 120), err);
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!blocked, egui::Button::new("Save"))
+                        .on_disabled_hover_text("Stop the recording before switching profiles.")
+                        .clicked()
+                    {
+                        confirm = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if confirm {
+            self.confirm_profile_dialog();
+        } else if cancel {
+            self.profile_dialog = None;
+        }
+    }
+
+    fn close_dialog_window(&mut self, ctx: &egui::Context) {
+        if !self.close_dialog_open {
+            return;
+        }
+        let mut stop_and_quit = false;
+        let mut keep_recording = false;
+        let mut cancel = false;
+        egui::Window::new("Recording in progress")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("wf-recorder is still recording. What should happen to it?");
+                ui.label(
+                    RichText::new(
+                        "Keeping it in the background minimizes this window; relaunch wf-recorder-ui or use `wf-recorder-ui ctl stop` to finish.",
+                    )
+                    .small(),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Stop, finalize and quit").clicked() {
+                        stop_and_quit = true;
+                    }
+                    if ui.button("Keep recording in background").clicked() {
+                        keep_recording = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if stop_and_quit {
+            self.stop_recording();
+            self.quit_after_stop = true;
+        } else if keep_recording {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+        if stop_and_quit || keep_recording || cancel {
+            self.close_dialog_open = false;
+        }
+    }
+
+    fn collision_dialog_window(&mut self, ctx: &egui::Context) {
+        let Some(output_file) = &self.collision_prompt else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("File already exists")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(output_file).monospace());
+                ui.label("A file with this name already exists.");
+                ui.horizontal(|ui| {
+                    if ui.button("Overwrite").clicked() {
+                        choice = Some(Some(CollisionPolicy::Overwrite));
+                    }
+                    if ui.button("Add -1, -2, … suffix").clicked() {
+                        choice = Some(Some(CollisionPolicy::Suffix));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(None);
+                    }
+                });
+            });
+        if let Some(policy) = choice {
+            self.resolve_collision(policy);
+        }
+    }
+
+    fn log_viewer_window(&mut self, ctx: &egui::Context) {
+        let Some((path, contents)) = &self.log_viewer else {
+            return;
+        };
+        let mut open = true;
+        let mut copy = false;
+        egui::Window::new("Session log")
+            .open(&mut open)
+            .default_size([640.0, 420.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(path).monospace().small());
+                    if ui.small_button("Copy all").clicked() {
+                        copy = true;
+                    }
+                });
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let mut preview = contents.as_str();
+                    ui.add(
+                        TextEdit::multiline(&mut preview)
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
+        if copy {
+            ctx.output_mut(|o| o.copied_text = contents.clone());
+        }
+        if !open {
+            self.log_viewer = None;
+        }
+    }
+
+    fn trim_dialog_window(&mut self, ctx: &egui::Context) {
+        for uri in self.stale_thumbnails.drain(..) {
+            ctx.forget_image(&uri);
+        }
+        let Some(dialog) = &mut self.trim_dialog else {
+            return;
+        };
+        let mut open = true;
+        let mut start = false;
+        let mut refresh = Vec::new();
+        egui::Window::new("Trim recording")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(540.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&dialog.source).monospace().small());
+                let Some(duration) = dialog.duration else {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Reading the duration with ffprobe…");
+                    });
+                    return;
+                };
+                ui.add_enabled_ui(!dialog.running, |ui| {
+                    egui::Grid::new("trim_edges")
+                        .num_columns(2)
+                        .spacing([12.0, 6.0])
+                        .show(ui, |ui| {
+                            for (edge, label) in [(TrimEdge::Start, "Start"), (TrimEdge::End, "End")]
+                            {
+                                let value = match edge {
+                                    TrimEdge::Start => &mut dialog.start,
+                                    TrimEdge::End => &mut dialog.end,
+                                };
+                                ui.label(label);
+                                let response = ui.add(
+                                    egui::Slider::new(value, 0.0..=duration)
+                                        .custom_formatter(|value, _| format_timestamp(value))
+                                        .step_by(0.05),
+                                );
+                                if response.drag_stopped()
+                                    || (response.changed() && !response.dragged())
+                                {
+                                    refresh.push(edge);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    for thumbnail in &dialog.thumbnails {
+                        match thumbnail {
+                            Some(Ok(thumbnail)) => {
+                                ui.add(
+                                    Image::from_bytes(thumbnail.uri.clone(), thumbnail.png.clone())
+                                        .max_width(240.0),
+                                );
+                            }
+                            Some(Err(err)) => {
+                                ui.add_sized(
+                                    [240.0, 135.0],
+                                    egui::Label::new(RichText::new(err).weak()).wrap(true),
+                                );
+                            }
+                            None => {
+                                ui.add_sized([240.0, 135.0], Spinner::new());
+                            }
+                        }
+                    }
+                });
+
+                let length = dialog.end - dialog.start;
+                if length > 0.0 {
+                    ui.label(format!(
+                        "Keeps {} of {}.",
+                        format_timestamp(length),
+                        format_timestamp(duration)
+                    ));
+                } else {
+                    ui.colored_label(Color32::RED, "The end must come after the start.");
+                }
+                ui.add_enabled(
+                    !dialog.running,
+                    egui::Checkbox::new(&mut dialog.exact, "Frame-exact cut (re-encode)"),
+                )
+                .on_hover_text(
+                    "Stream copy is instant and lossless but starts at the keyframe before the chosen start. Re-encoding cuts on the exact frame; it is slower and also used when a stream copy fails.",
+                );
+                if let Some(err) = &dialog.error {
+                    ui.colored_label(Color32::RED, err);
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!dialog.running && length > 0.0, egui::Button::new("Trim"))
+                        .on_hover_text(format!("Writes {}", dialog.output))
+                        .clicked()
+                    {
+                        start = true;
+                    }
+                    if dialog.running {
+                        ui.spinner();
+                        ui.label("Running ffmpeg…");
+                    }
+                });
+            });
+        for edge in refresh {
+            dialog.request_thumbnail(edge);
+        }
+        if start {
+            self.start_trim();
+        }
+        if !open && let Some(dialog) = self.trim_dialog.take() {
+            self.close_trim_dialog(dialog);
+        }
+    }
+
+    fn export_dialog_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.export_dialog else {
+            return;
+        };
+        let running = dialog.running();
+        let mut open = true;
+        let mut start = false;
+        egui::Window::new("Export recording")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&dialog.source).monospace().small());
+                ui.add_enabled_ui(!running, |ui| {
+                    egui::ComboBox::from_id_source("export_preset")
+                        .selected_text(dialog.preset.label())
+                        .show_ui(ui, |ui| {
+                            for preset in ExportPreset::ALL {
+                                ui.selectable_value(&mut dialog.preset, preset, preset.label());
+                            }
+                        });
+                    ui.weak(dialog.preset.description());
+                    if dialog.preset == ExportPreset::TargetSize {
+                        ui.horizontal(|ui| {
+                            ui.label("Size cap");
+                            ui.add(
+                                egui::DragValue::new(&mut dialog.target_mb)
+                                    .clamp_range(1.0..=100_000.0)
+                                    .speed(0.5)
+                                    .suffix(" MB"),
+                            );
+                            for cap in [8.0, 10.0, 25.0, 50.0] {
+                                if ui.small_button(format!("{cap} MB")).clicked() {
+                                    dialog.target_mb = cap;
+                                }
+                            }
+                        });
+                    }
+                });
+                if let Some(progress) = dialog.progress {
+                    ui.add(egui::ProgressBar::new(progress).show_percentage());
+                }
+                if let Some(err) = &dialog.error {
+                    ui.colored_label(Color32::RED, err);
+                }
+                ui.horizontal(|ui| {
+                    if running {
+                        if ui.button("Cancel").clicked() {
+                            dialog.cancel();
+                        }
+                    } else if ui
+                        .button("Export")
+                        .on_hover_text("Writes a new file next to the recording.")
+                        .clicked()
+                    {
+                        start = true;
+                    }
+                });
+            });
+        if start {
+            self.start_export();
+        }
+        if !open {
+            match &self.export_dialog {
+                // ffmpeg is stopped first; the dialog closes once it exits.
+                Some(dialog) if dialog.running() => dialog.cancel(),
+                _ => self.export_dialog = None,
+            }
+        }
+    }
+
+    fn stray_recorders_window(&mut self, ctx: &egui::Context) {
+        if self.stray_recorders.is_empty() {
+            return;
+        }
+        let recording = self.status.is_active();
+        let mut action = None;
+        egui::Window::new("wf-recorder is still running")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("A previous session exited without stopping these recordings:");
+                for (index, record) in self.stray_recorders.iter().enumerate() {
+                    ui.separator();
+                    ui.label(format!(
+                        "pid {} → {} (running for {}s)",
+                        record.pid,
+                        record.output_file,
+                        record.running_for().as_secs()
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(!recording, egui::Button::new("Adopt"))
+                            .on_hover_text("Track it here as the current recording.")
+                            .clicked()
+                        {
+                            action = Some((index, StrayAction::Adopt));
+                        }
+                        if ui
+                            .add_enabled(!recording, egui::Button::new("Stop"))
+                            .on_hover_text("Stop it gracefully so the file is finalized.")
+                            .clicked()
+                        {
+                            action = Some((index, StrayAction::Stop));
+                        }
+                        if ui
+                            .button("Kill")
+                            .on_hover_text("Send SIGKILL; the file may be incomplete.")
+                            .clicked()
+                        {
+                            action = Some((index, StrayAction::Kill));
+                        }
+                        if ui.button("Ignore").clicked() {
+                            action = Some((index, StrayAction::Ignore));
+                        }
+                    });
+                }
+            });
+        match action {
+            Some((index, StrayAction::Adopt)) => {
+                self.adopt_stray_recorder(index);
+            }
+            Some((index, StrayAction::Stop)) => self.stop_stray_recorder(index),
+            Some((index, StrayAction::Kill)) => self.kill_stray_recorder(index),
+            Some((index, StrayAction::Ignore)) => {
+                self.stray_recorders.remove(index);
+            }
+            None => {}
+        }
+    }
+
     fn general_section(&mut self, ui: &mut egui::Ui, width: f32) {
         ui.set_width(width);
         let field_width = (width * 0.65).max(width - 140.0).clamp(120.0, width);
@@ -282,7 +928,7 @@ impl RecorderApp {
                 label_with_help(
                     ui,
                     "File template",
-                    "Set -f/--file. Supports $timestamp and $format placeholders.",
+                    "Set -f/--file. Placeholders: $timestamp, $date, $time, $format, $output (monitor), $app and $title (selected window), $mode, $profile, $hostname, $n (first unused number) and $segment (chunk number when splitting). Values are made filesystem-safe.",
                 );
                 ui.add(
                     TextEdit::singleline(&mut self.config.file_template)
@@ -294,25 +940,73 @@ impl RecorderApp {
                 label_with_help(
                     ui,
                     "File format",
-                    "Replaces $format in the template and determines the default extension.",
+                    "Replaces $format in the template and determines the default extension. Type any extension the installed ffmpeg can mux.",
                 );
                 let selected_format_label = COMMON_OUTPUT_FORMATS
                     .iter()
                     .find(|(_, value)| *value == self.config.file_format)
                     .map(|(label, _)| (*label).to_string())
                     .unwrap_or_else(|| self.config.file_format.clone());
-                egui::ComboBox::from_id_source("output_format_combo")
-                    .width(field_width.min(ui.available_width()))
-                    .selected_text(selected_format_label)
-                    .show_ui(ui, |ui| {
-                        for (label, value) in COMMON_OUTPUT_FORMATS.iter() {
-                            ui.selectable_value(
-                                &mut self.config.file_format,
-                                (*value).to_string(),
-                                *label,
-                            );
-                        }
-                    });
+                let muxers = &self.ffmpeg_capabilities.muxers;
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("output_format_combo")
+                        .width(140.0)
+                        .selected_text(selected_format_label)
+                        .show_ui(ui, |ui| {
+                            for (label, value) in COMMON_OUTPUT_FORMATS.iter() {
+                                let muxer = muxer_for_extension(value);
+                                let available = muxers.is_empty()
+                                    || muxers.iter().any(|entry| entry.name == muxer);
+                                let response = ui.selectable_value(
+                                    &mut self.config.file_format,
+                                    (*value).to_string(),
+                                    *label,
+                                );
+                                if !available {
+                                    response.on_hover_text("Not reported by the installed ffmpeg.");
+                                }
+                            }
+                        });
+                    ui.add(
+                        TextEdit::singleline(&mut self.config.file_format)
+                            .desired_width((field_width - 150.0).min(ui.available_width()))
+                            .hint_text("mp4"),
+                    );
+                });
+                let muxer = muxer_for_extension(&self.config.file_format);
+                if !muxers.is_empty()
+                    && !muxer.is_empty()
+                    && self.config.muxer.trim().is_empty()
+                    && !muxers.iter().any(|entry| entry.name == muxer)
+                {
+                    ui.end_row();
+                    ui.label("");
+                    ui.colored_label(
+                        Color32::from_rgb(255, 200, 120),
+                        format!(
+                            "The installed ffmpeg has no {muxer} muxer; pick one under Muxer/Container."
+                        ),
+                    );
+                }
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Date / time format",
+                    "strftime formats used by $date and $time, e.g. %Y%m%d or %H.%M.",
+                );
+                ui.horizontal(|ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut self.config.date_format)
+                            .desired_width(110.0)
+                            .hint_text("%Y-%m-%d"),
+                    );
+                    ui.add(
+                        TextEdit::singleline(&mut self.config.time_format)
+                            .desired_width(110.0)
+                            .hint_text("%H-%M-%S"),
+                    );
+                });
                 ui.end_row();
 
                 label_with_help(
@@ -320,7 +1014,7 @@ impl RecorderApp {
                     "Resolved path",
                     "Shows the file path that will be used when recording starts.",
                 );
-                match self.config.preview_output_file() {
+                match self.output_preview().file.clone() {
                     Ok(path) => {
                         ui.label(RichText::new(path).monospace());
                     }
@@ -479,6 +1173,10 @@ impl RecorderApp {
                                             self.config.selected_window_id = window.id.clone();
                                             self.config.selected_window_geometry =
                                                 window.geometry.clone();
+                                            self.config.selected_window_app =
+                                                window.app.clone();
+                                            self.config.selected_window_title =
+                                                window.title.clone();
                                         }
                                     }
                                 });
@@ -510,44 +1208,120 @@ impl RecorderApp {
                         ui.end_row();
                     }
                 }
-            });
-    }
 
-    fn video_section(&mut self, ui: &mut egui::Ui, width: f32) {
-        ui.set_width(width);
-        let field_width = (width * 0.65).max(width - 140.0).clamp(120.0, width);
-        egui::Grid::new("video_grid")
-            .num_columns(2)
-            .spacing([16.0, 8.0])
-            .striped(true)
-            .show(ui, |ui| {
                 label_with_help(
                     ui,
-                    "Video codec",
-                    "Sets -c/--codec. Pick a preset or type a custom encoder name.",
+                    "Countdown",
+                    "Seconds to wait after Start before wf-recorder is launched, e.g. to move this window out of frame.",
                 );
-                let codec_label = COMMON_VIDEO_CODECS
-                    .iter()
-                    .find(|(_, value)| *value == self.config.codec)
-                    .map(|(label, _)| *label)
-                    .unwrap_or("Custom");
-                egui::ComboBox::from_id_source("codec_combo")
-                    .width(field_width.min(ui.available_width()))
-                    .selected_text(codec_label)
-                    .show_ui(ui, |ui| {
-                        for (label, value) in COMMON_VIDEO_CODECS {
-                            ui.selectable_value(
-                                &mut self.config.codec,
-                                value.to_string(),
-                                label.to_owned(),
-                            );
-                        }
-                    });
                 ui.add(
-                    TextEdit::singleline(&mut self.config.codec)
+                    egui::DragValue::new(&mut self.config.countdown_secs)
+                        .clamp_range(0..=600)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Start at",
+                    "Wait until this local time (HH:MM or HH:MM:SS) before recording; a time already past today means tomorrow. Overrides the countdown.",
+                );
+                ui.add(
+                    TextEdit::singleline(&mut self.scheduled_start)
+                        .desired_width(field_width.min(ui.available_width()))
+                        .hint_text("Leave empty to start right away"),
+                );
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Stop after",
+                    "Stop gracefully once this much has been recorded (paused time does not count). 0 records until you stop.",
+                );
+                ui.horizontal(|ui| {
+                    let mut minutes = self.config.max_duration_secs / 60;
+                    let mut seconds = self.config.max_duration_secs % 60;
+                    let changed = ui
+                        .add(egui::DragValue::new(&mut minutes).suffix(" min"))
+                        .changed()
+                        | ui
+                            .add(
+                                egui::DragValue::new(&mut seconds)
+                                    .clamp_range(0..=59)
+                                    .suffix(" s"),
+                            )
+                            .changed();
+                    if changed {
+                        self.config.max_duration_secs = minutes * 60 + seconds;
+                    }
+                });
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Max file size",
+                    "Stop gracefully once the recording reaches this size. 0 disables the limit.",
+                );
+                ui.add(egui::DragValue::new(&mut self.config.max_file_size_mb).suffix(" MB"));
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Split into chunks",
+                    "Restart wf-recorder into a new file every N minutes and/or megabytes so a crash only loses the current chunk. Files are numbered through $segment (appended to the name if the template lacks it). 0 disables each limit.",
+                );
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.config.segment_minutes)
+                            .prefix("every ")
+                            .suffix(" min"),
+                    );
+                    ui.label("or");
+                    ui.add(egui::DragValue::new(&mut self.config.segment_size_mb).suffix(" MB"));
+                });
+                ui.end_row();
+            });
+    }
+
+    fn video_section(&mut self, ui: &mut egui::Ui, width: f32) {
+        ui.set_width(width);
+        let field_width = (width * 0.65).max(width - 140.0).clamp(120.0, width);
+        egui::Grid::new("video_grid")
+            .num_columns(2)
+            .spacing([16.0, 8.0])
+            .striped(true)
+            .show(ui, |ui| {
+                label_with_help(
+                    ui,
+                    "Quality",
+                    "Picks codec parameters, pixel format, framerate and audio bitrate for the selected codecs. Editing any of them switches to Manual.",
+                );
+                self.quality_picker(ui, field_width);
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Video codec",
+                    "Sets -c/--codec. Pick a preset or type a custom encoder name.",
+                );
+                let previous_codec = self.config.codec.clone();
+                ffmpeg_picker(
+                    ui,
+                    "codec_combo",
+                    &mut self.config.codec,
+                    &COMMON_VIDEO_CODECS,
+                    &self.ffmpeg_capabilities.video_encoders,
+                    None,
+                    field_width.min(ui.available_width()),
+                );
+                ui.add(
+                    TextEdit::singleline(&mut self.config.codec)
                         .desired_width(field_width.min(ui.available_width()))
                         .hint_text("libx264"),
                 );
+                if self.config.codec != previous_codec && self.config.codec.ends_with("_vaapi") {
+                    self.apply_vaapi_setup();
+                }
                 ui.end_row();
 
                 label_with_help(
@@ -567,6 +1341,7 @@ impl RecorderApp {
                     ui,
                     "Codec parameter",
                     "Adds -p/--codec-param entries (format: key=value).",
+                    schema_for(&self.config.codec),
                     &mut self.config.codec_params,
                     field_width,
                 );
@@ -577,10 +1352,21 @@ impl RecorderApp {
                     "Pixel format",
                     "Sets -x/--pixel-format. Leave blank for wf-recorder default.",
                 );
-                ui.add(
-                    TextEdit::singleline(&mut self.config.pixel_format)
-                        .desired_width(field_width.min(ui.available_width())),
-                );
+                ui.horizontal(|ui| {
+                    ffmpeg_picker(
+                        ui,
+                        "pixel_format_combo",
+                        &mut self.config.pixel_format,
+                        &[],
+                        &self.ffmpeg_capabilities.pixel_formats,
+                        Some("Default"),
+                        140.0,
+                    );
+                    ui.add(
+                        TextEdit::singleline(&mut self.config.pixel_format)
+                            .desired_width((field_width - 150.0).min(ui.available_width())),
+                    );
+                });
                 ui.end_row();
 
                 label_with_help(
@@ -588,17 +1374,68 @@ impl RecorderApp {
                     "Muxer/Container",
                     "Sets -m/--muxer. Overridden automatically by --file format when omitted.",
                 );
-                ui.add(
-                    TextEdit::singleline(&mut self.config.muxer)
-                        .desired_width(field_width.min(ui.available_width())),
-                );
+                ui.horizontal(|ui| {
+                    ffmpeg_picker(
+                        ui,
+                        "muxer_combo",
+                        &mut self.config.muxer,
+                        &[],
+                        &self.ffmpeg_capabilities.muxers,
+                        Some("From file format"),
+                        140.0,
+                    );
+                    ui.add(
+                        TextEdit::singleline(&mut self.config.muxer)
+                            .desired_width((field_width - 150.0).min(ui.available_width())),
+                    );
+                });
                 ui.end_row();
 
                 label_with_help(
                     ui,
                     "VAAPI device",
-                    "Sets -d/--device (for hardware accelerated encoders).",
+                    "Sets -d/--device (for hardware accelerated encoders). Render nodes found under /dev/dri are listed with their kernel driver.",
                 );
+                ui.horizontal(|ui| {
+                    let selected = self
+                        .render_nodes
+                        .iter()
+                        .find(|node| node.path == self.config.encoding_device.trim())
+                        .map(RenderNode::label)
+                        .unwrap_or_else(|| match self.config.encoding_device.trim() {
+                            "" => "None".to_string(),
+                            device => device.to_string(),
+                        });
+                    egui::ComboBox::from_id_source("encoding_device_combo")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut self.config.encoding_device,
+                                String::new(),
+                                "None",
+                            );
+                            if self.render_nodes.is_empty() {
+                                ui.weak("No render nodes found.");
+                            }
+                            for node in &self.render_nodes {
+                                ui.selectable_value(
+                                    &mut self.config.encoding_device,
+                                    node.path.clone(),
+                                    node.label(),
+                                );
+                            }
+                        });
+                    if ui
+                        .small_button("Rescan")
+                        .on_hover_text("List the render nodes under /dev/dri again.")
+                        .clicked()
+                    {
+                        self.refresh_render_nodes();
+                    }
+                });
+                ui.end_row();
+
+                ui.label("");
                 ui.add(
                     TextEdit::singleline(&mut self.config.encoding_device)
                         .desired_width(field_width.min(ui.available_width()))
@@ -606,6 +1443,33 @@ impl RecorderApp {
                 );
                 ui.end_row();
 
+                if let Some(setup) = suggest_vaapi_setup(&self.config, &self.render_nodes) {
+                    label_with_help(
+                        ui,
+                        "VAAPI setup",
+                        "What the selected VAAPI encoder still needs, from the wf-recorder manpage.",
+                    );
+                    ui.vertical(|ui| {
+                        let suggestions = [
+                            ("Device", &setup.device),
+                            ("Filter", &setup.filter),
+                            ("Pixel format", &setup.pixel_format),
+                        ];
+                        for (name, value) in suggestions {
+                            if let Some(value) = value {
+                                ui.colored_label(
+                                    Color32::from_rgb(255, 200, 120),
+                                    format!("{name}: {value}"),
+                                );
+                            }
+                        }
+                        if ui.button("Apply suggested settings").clicked() {
+                            self.apply_vaapi_setup();
+                        }
+                    });
+                    ui.end_row();
+                }
+
                 label_with_help(
                     ui,
                     "Filters",
@@ -635,7 +1499,169 @@ impl RecorderApp {
                     );
                 });
                 ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "If file exists",
+                    "What to do when the output file already exists and --overwrite is off. Headless recordings treat Ask as Refuse.",
+                );
+                ui.add_enabled_ui(!self.config.overwrite, |ui| {
+                    egui::ComboBox::from_id_source("collision_policy_combo")
+                        .selected_text(self.config.collision_policy.label())
+                        .show_ui(ui, |ui| {
+                            for policy in [
+                                CollisionPolicy::Ask,
+                                CollisionPolicy::Overwrite,
+                                CollisionPolicy::Suffix,
+                                CollisionPolicy::Fail,
+                            ] {
+                                ui.selectable_value(
+                                    &mut self.config.collision_policy,
+                                    policy,
+                                    policy.label(),
+                                );
+                            }
+                        });
+                });
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Metadata",
+                    "Write <file>.json next to each recording with the exact wf-recorder command, these settings, start/stop times, exit status, capture target and wf-recorder version.",
+                );
+                ui.checkbox(&mut self.config.write_metadata, "Write a .json sidecar");
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Verify",
+                    "Run ffprobe on each finished file and warn about missing video/audio streams, an unexpected codec, or a duration far from the recording time (e.g. a truncated MP4).",
+                );
+                ui.checkbox(
+                    &mut self.config.verify_recordings,
+                    "Check finished files with ffprobe",
+                );
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Crash safety",
+                    "Record into a Matroska file next to the output and remux it into the chosen format after a clean stop. If wf-recorder is killed or crashes the .mkv stays playable. Not used with segmented recordings or a custom muxer.",
+                );
+                ui.checkbox(
+                    &mut self.config.record_as_mkv,
+                    "Record to MKV, remux after a clean stop",
+                );
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Stop timeout",
+                    "Seconds to wait after Stop sends SIGINT before escalating to SIGTERM, and again before SIGKILL. Long recordings can take a while to finalize.",
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.config.stop_timeout_secs)
+                        .clamp_range(1..=600)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+
+                label_with_help(
+                    ui,
+                    "Auto-restart",
+                    "If wf-recorder exits with an error mid-recording (output hotplug, audio server hiccup), start it again into a new segment. The wait doubles after each attempt, up to a minute.",
+                );
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.config.restart_on_failure, "Restart on failure");
+                    ui.add_enabled_ui(self.config.restart_on_failure, |ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut self.config.max_restarts)
+                                .clamp_range(1..=50)
+                                .prefix("up to ")
+                                .suffix("×"),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut self.config.restart_backoff_secs)
+                                .clamp_range(0..=60)
+                                .prefix("wait ")
+                                .suffix(" s"),
+                        );
+                    });
+                });
+                ui.end_row();
+            });
+    }
+
+    fn quality_picker(&mut self, ui: &mut egui::Ui, width: f32) {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                let selected = self
+                    .config
+                    .quality_preset
+                    .map_or("Manual", QualityPreset::label);
+                egui::ComboBox::from_id_source("quality_preset_combo")
+                    .selected_text(selected)
+                    .width(width.min(ui.available_width()).min(200.0))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.config.quality_preset, None, "Manual")
+                            .on_hover_text("Edit every encoder setting yourself.");
+                        for preset in QualityPreset::ALL {
+                            ui.selectable_value(
+                                &mut self.config.quality_preset,
+                                Some(preset),
+                                preset.label(),
+                            )
+                            .on_hover_text(preset.description());
+                        }
+                    });
+                if self.config.quality_preset.is_some()
+                    && ui
+                        .button("Detach")
+                        .on_hover_text("Keep the settings below but edit them by hand.")
+                        .clicked()
+                {
+                    self.config.quality_preset = None;
+                }
             });
+            let Some(preset) = self.config.quality_preset else {
+                return;
+            };
+            ui.weak(preset.description());
+            let audio = self.config.audio_mode != AudioMode::None;
+            let settings = effective_settings(preset, &self.config.codec, &self.config.audio_codec);
+            egui::CollapsingHeader::new("Effective settings")
+                .id_source("quality_effective_settings")
+                .show(ui, |ui| {
+                    let params = match &settings.codec_params {
+                        Some(params) if params.is_empty() => "none".to_string(),
+                        Some(params) => params
+                            .iter()
+                            .filter_map(ParamEntry::format)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        None => "unchanged".to_string(),
+                    };
+                    ui.label(format!("Codec parameters: {params}"));
+                    ui.label(format!(
+                        "Pixel format: {}",
+                        settings.pixel_format.as_deref().unwrap_or("unchanged")
+                    ));
+                    ui.label(match settings.framerate.as_str() {
+                        "" => "Framerate: variable (as the screen updates)".to_string(),
+                        framerate => format!("Framerate: constant {framerate} fps"),
+                    });
+                    if audio {
+                        ui.label(format!(
+                            "Audio bitrate: {}",
+                            settings.audio_bitrate.as_deref().unwrap_or("lossless")
+                        ));
+                    }
+                    for note in &settings.notes {
+                        ui.colored_label(Color32::from_rgb(255, 200, 120), note);
+                    }
+                });
+        });
     }
 
     fn audio_section(&mut self, ui: &mut egui::Ui, width: f32) {
@@ -809,23 +1835,15 @@ impl RecorderApp {
                             "Audio codec",
                             "Sets --audio-codec. Pick a preset or type the encoder name.",
                         );
-                        let codec_label = COMMON_AUDIO_CODECS
-                            .iter()
-                            .find(|(_, value)| *value == self.config.audio_codec)
-                            .map(|(label, _)| *label)
-                            .unwrap_or("Custom");
-                        egui::ComboBox::from_id_source("audio_codec_combo")
-                            .width(control_width.min(ui.available_width()))
-                            .selected_text(codec_label)
-                            .show_ui(ui, |ui| {
-                                for (label, value) in COMMON_AUDIO_CODECS {
-                                    ui.selectable_value(
-                                        &mut self.config.audio_codec,
-                                        value.to_string(),
-                                        label.to_owned(),
-                                    );
-                                }
-                            });
+                        ffmpeg_picker(
+                            ui,
+                            "audio_codec_combo",
+                            &mut self.config.audio_codec,
+                            &COMMON_AUDIO_CODECS,
+                            &self.ffmpeg_capabilities.audio_encoders,
+                            None,
+                            control_width.min(ui.available_width()),
+                        );
                         ui.end_row();
 
                         let label = ui.label(RichText::new("Audio codec parameters").strong());
@@ -834,6 +1852,7 @@ impl RecorderApp {
                             ui,
                             "",
                             "Adds -P/--audio-codec-param entries (format: key=value).",
+                            schema_for(&self.config.audio_codec),
                             &mut self.config.audio_codec_params,
                             control_width,
                         );
@@ -854,12 +1873,25 @@ impl RecorderApp {
                         label_with_help(
                             ui,
                             "Sample format",
-                            "Sets -X/--sample-format. Use `ffmpeg -sample_fmts` for options.",
-                        );
-                        ui.add(
-                            TextEdit::singleline(&mut self.config.sample_format)
-                                .desired_width(control_width.min(ui.available_width())),
+                            "Sets -X/--sample-format. The list comes from `ffmpeg -sample_fmts`.",
                         );
+                        ui.horizontal(|ui| {
+                            ffmpeg_picker(
+                                ui,
+                                "sample_format_combo",
+                                &mut self.config.sample_format,
+                                &[],
+                                &self.ffmpeg_capabilities.sample_formats,
+                                Some("Default"),
+                                140.0,
+                            );
+                            ui.add(
+                                TextEdit::singleline(&mut self.config.sample_format)
+                                    .desired_width(
+                                        (control_width - 150.0).min(ui.available_width()),
+                                    ),
+                            );
+                        });
                         ui.end_row();
                     });
             });
@@ -890,6 +1922,30 @@ impl RecorderApp {
                 self.invoke_simple_action(SimpleAction::Help);
             }
         });
+        ui.horizontal_wrapped(|ui| {
+            if self.ffmpeg_capabilities_loading {
+                ui.spinner();
+            }
+            if let Some(err) = &self.ffmpeg_capabilities_error {
+                ui.colored_label(Color32::RED, err);
+            } else if self.ffmpeg_capabilities.is_empty() {
+                ui.weak("Reading encoders and formats from ffmpeg…");
+            } else {
+                ui.weak(self.ffmpeg_capabilities.summary());
+            }
+            if ui
+                .add_enabled(
+                    !self.ffmpeg_capabilities_loading,
+                    egui::Button::new("Rescan ffmpeg"),
+                )
+                .on_hover_text(
+                    "Re-read the encoders, muxers and pixel/sample formats offered in the Encoding and Audio pickers.",
+                )
+                .clicked()
+            {
+                self.request_ffmpeg_capabilities_refresh();
+            }
+        });
 
         if let Some(output) = &self.last_action_output {
             ui.add_space(8.0);
@@ -908,6 +1964,18 @@ impl RecorderApp {
             }
         }
 
+        ui.add_space(8.0);
+        self.recovery_tool(ui);
+
+        ui.add_space(6.0);
+        ui.checkbox(
+            &mut self.allow_concurrent_sessions,
+            "Allow concurrent recordings",
+        )
+        .on_hover_text(
+            "Start wf-recorder even if another wf-recorder-ui window or `wf-recorder-ui record` is already recording.",
+        );
+
         ui.add_space(6.0);
         ui.horizontal_wrapped(|ui| {
             if ui
@@ -937,6 +2005,275 @@ impl RecorderApp {
         });
     }
 
+    fn recovery_tool(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Recover a recording").strong());
+        if !is_crash_resilient(&self.config.file_format) && !self.config.record_as_mkv {
+            ui.colored_label(
+                Color32::from_rgb(255, 200, 120),
+                format!(
+                    "{} files are unplayable if wf-recorder is killed before it finishes them. Matroska keeps everything recorded up to the crash.",
+                    self.config.file_format.to_uppercase()
+                ),
+            );
+            ui.horizontal_wrapped(|ui| {
+                if ui.button("Record to MKV").clicked() {
+                    self.config.file_format = "mkv".to_string();
+                }
+                if ui
+                    .button("Record to MKV, remux after stop")
+                    .on_hover_text(
+                        "Keep the current format for finished files but capture into .mkv first.",
+                    )
+                    .clicked()
+                {
+                    self.config.record_as_mkv = true;
+                }
+            });
+        }
+
+        let busy = self.recovery_receiver.is_some();
+        let mut start = None;
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.recovery_path)
+                    .hint_text("Path to a broken recording")
+                    .desired_width(320.0),
+            );
+            if ui
+                .add_enabled(!busy, egui::Button::new("Check & recover"))
+                .on_hover_text(
+                    "Probe the file with ffprobe and, if it is broken, copy whatever ffmpeg can still read into <name>.recovered.mkv.",
+                )
+                .clicked()
+            {
+                start = Some(self.recovery_path.clone());
+            }
+            if busy {
+                ui.spinner();
+            }
+        });
+
+        let suspects: Vec<_> = self
+            .history
+            .entries
+            .iter()
+            .filter(|entry| !entry.missing && !entry.warnings.is_empty())
+            .collect();
+        if !suspects.is_empty() {
+            ui.weak("Recordings that failed their integrity check:");
+        }
+        for entry in suspects {
+            ui.horizontal_wrapped(|ui| {
+                if ui
+                    .add_enabled(!busy, egui::Button::new("Recover"))
+                    .clicked()
+                {
+                    start = Some(entry.file.clone());
+                }
+                ui.label(entry.file_name());
+                ui.weak(&entry.warnings[0]);
+            });
+        }
+
+        match &self.recovery_result {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::RED, err);
+            }
+            None => {}
+        }
+        if let Some(file) = start {
+            self.start_recovery(file);
+        }
+    }
+
+    fn library_section(&mut self, ui: &mut egui::Ui, width: f32) {
+        ui.set_width(width);
+        ui.horizontal(|ui| {
+            ui.label(format!("{} recordings", self.history.entries.len()));
+            if ui
+                .button("Refresh")
+                .on_hover_text("Check again which files still exist.")
+                .clicked()
+            {
+                self.history.refresh();
+            }
+        });
+        if self.history.entries.is_empty() {
+            ui.label("Finished recordings show up here.");
+            return;
+        }
+
+        let mut action = None;
+        for (index, entry) in self.history.entries.iter().enumerate() {
+            ui.separator();
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new(entry.file_name()).strong());
+                if entry.missing {
+                    ui.colored_label(
+                        Color32::from_rgb(255, 120, 120),
+                        "missing (moved or deleted)",
+                    );
+                }
+            });
+            let mut details = vec![
+                entry.recorded_at.clone(),
+                format_duration(std::time::Duration::from_secs_f64(entry.duration_secs)),
+                format_file_size(entry.file_size),
+            ];
+            details.extend(
+                [
+                    &entry.details.profile,
+                    &entry.details.capture_mode,
+                    &entry.details.geometry,
+                    &entry.details.codec,
+                    &entry.exit_status,
+                ]
+                .into_iter()
+                .filter(|value| !value.is_empty())
+                .cloned(),
+            );
+            ui.weak(details.join(" · "));
+            if let Some(derivation) = &entry.derived_from {
+                ui.weak(format!(
+                    "{} from {}",
+                    derivation.operation,
+                    Path::new(&derivation.source)
+                        .file_name()
+                        .map(|name| name.to_string_lossy())
+                        .unwrap_or_default()
+                ));
+            }
+            ui.label(RichText::new(&entry.file).monospace().small());
+            for warning in &entry.warnings {
+                ui.colored_label(Color32::from_rgb(255, 200, 120), format!("⚠ {warning}"));
+            }
+
+            match &mut self.library_rename {
+                Some((rename_index, name)) if *rename_index == index => {
+                    ui.horizontal(|ui| {
+                        let response = ui.add(TextEdit::singleline(name).desired_width(260.0));
+                        if ui.button("Save").clicked()
+                            || (response.lost_focus()
+                                && ui.input(|input| input.key_pressed(Key::Enter)))
+                        {
+                            action = Some(LibraryAction::Rename(index));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            action = Some(LibraryAction::CancelRename);
+                        }
+                    });
+                    continue;
+                }
+                _ => {}
+            }
+            if self.library_delete == Some(index) {
+                ui.horizontal(|ui| {
+                    ui.label("Delete this file from disk?");
+                    if ui.button("Delete").clicked() {
+                        action = Some(LibraryAction::Delete(index));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = Some(LibraryAction::CancelDelete);
+                    }
+                });
+                continue;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.add_enabled_ui(!entry.missing, |ui| {
+                    if ui.button("Open").clicked() {
+                        action = Some(LibraryAction::Open(index));
+                    }
+                    if ui.button("Open folder").clicked() {
+                        action = Some(LibraryAction::OpenFolder(index));
+                    }
+                });
+                if ui.button("Copy path").clicked() {
+                    ui.output_mut(|o| o.copied_text = entry.file.clone());
+                }
+                if entry.log_file.is_some()
+                    && ui
+                        .button("Log")
+                        .on_hover_text("Show the wf-recorder output of this session.")
+                        .clicked()
+                {
+                    action = Some(LibraryAction::ShowLog(index));
+                }
+                ui.add_enabled_ui(!entry.missing, |ui| {
+                    if ui
+                        .button("Trim…")
+                        .on_hover_text("Cut the start and end into a new file.")
+                        .clicked()
+                    {
+                        action = Some(LibraryAction::Trim(index));
+                    }
+                    if ui
+                        .button("Export…")
+                        .on_hover_text(
+                            "Re-encode for sharing, to fit a size cap, or for archiving.",
+                        )
+                        .clicked()
+                    {
+                        action = Some(LibraryAction::Export(index));
+                    }
+                    if ui.button("Rename").clicked() {
+                        action = Some(LibraryAction::StartRename(index));
+                    }
+                    if ui.button("Delete").clicked() {
+                        action = Some(LibraryAction::ConfirmDelete(index));
+                    }
+                });
+                if ui
+                    .button("Forget")
+                    .on_hover_text("Remove from this list and keep the file.")
+                    .clicked()
+                {
+                    action = Some(LibraryAction::Forget(index));
+                }
+            });
+        }
+
+        match action {
+            Some(LibraryAction::Open(index)) => self.open_recording(index, false),
+            Some(LibraryAction::OpenFolder(index)) => self.open_recording(index, true),
+            Some(LibraryAction::ShowLog(index)) => self.show_recording_log(index),
+            Some(LibraryAction::Trim(index)) => self.open_trim_dialog(index),
+            Some(LibraryAction::Export(index)) => self.open_export_dialog(index),
+            Some(LibraryAction::StartRename(index)) => {
+                self.library_delete = None;
+                self.library_rename = Some((index, self.history.entries[index].file_name()));
+            }
+            Some(LibraryAction::Rename(index)) => {
+                let name = self
+                    .library_rename
+                    .take()
+                    .map(|(_, name)| name)
+                    .unwrap_or_default();
+                if !self.rename_recording(index, &name) {
+                    self.library_rename = Some((index, name));
+                }
+            }
+            Some(LibraryAction::CancelRename) => self.library_rename = None,
+            Some(LibraryAction::ConfirmDelete(index)) => {
+                self.library_rename = None;
+                self.library_delete = Some(index);
+            }
+            Some(LibraryAction::Delete(index)) => {
+                self.library_delete = None;
+                self.delete_recording(index);
+            }
+            Some(LibraryAction::CancelDelete) => self.library_delete = None,
+            Some(LibraryAction::Forget(index)) => {
+                self.library_rename = None;
+                self.library_delete = None;
+                self.forget_recording(index);
+            }
+            None => {}
+        }
+    }
+
     fn action_buttons(&mut self, ui: &mut egui::Ui, width: f32) {
         ui.set_width(width);
         ui.separator();
@@ -954,11 +2291,11 @@ impl RecorderApp {
         });
     }
 
-    fn command_preview(&self, ui: &mut egui::Ui, width: f32) {
+    fn command_preview(&mut self, ui: &mut egui::Ui, width: f32) {
         ui.set_width(width);
         ui.separator();
         ui.label("Command preview");
-        match self.build_command_preview() {
+        match self.output_preview().command.clone() {
             Ok(mut preview) => {
                 let preview_width = width.min(ui.available_width());
                 ui.add(
@@ -973,6 +2310,16 @@ impl RecorderApp {
                 ui.colored_label(Color32::from_rgb(255, 120, 120), err);
             }
         }
+        for issue in check_compatibility(&self.config) {
+            match issue.severity {
+                Severity::Error => {
+                    ui.colored_label(Color32::from_rgb(255, 120, 120), issue.message);
+                }
+                Severity::Warning => {
+                    ui.colored_label(Color32::from_rgb(255, 200, 120), issue.message);
+                }
+            }
+        }
     }
 
     fn recording_controls(&mut self, ui: &mut egui::Ui, width: f32) {
@@ -981,6 +2328,9 @@ impl RecorderApp {
         let mut start_requested = false;
         let mut stop_requested = false;
         let mut force_requested = false;
+        let mut pause_requested = false;
+        let mut resume_requested = false;
+        let mut start_now_requested = false;
 
         match &self.status {
             RecorderStatus::Idle => {
@@ -989,21 +2339,55 @@ impl RecorderApp {
                 let start_button = egui::Button::new("Start recording")
                     .min_size(egui::vec2(button_width, 40.0))
                     .wrap(true);
+                let blocked = first_error(&check_compatibility(&self.config)).is_some();
                 if ui
-                    .add(start_button)
+                    .add_enabled(!blocked, start_button)
                     .on_hover_text("Launch wf-recorder with the options above.")
+                    .on_disabled_hover_text("Fix the errors under the command preview first.")
                     .clicked()
                 {
                     start_requested = true;
                 }
             }
+            RecorderStatus::Scheduled(pending) => {
+                let remaining = format_duration(pending.remaining_rounded());
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        Color32::from_rgb(120, 210, 255),
+                        format!("Starting in {remaining}…"),
+                    );
+                    if ui
+                        .button("Start now")
+                        .on_hover_text("Skip the wait and launch wf-recorder immediately.")
+                        .clicked()
+                    {
+                        start_now_requested = true;
+                    }
+                    if ui
+                        .button("Cancel (Ctrl+C)")
+                        .on_hover_text("Cancel the scheduled recording.")
+                        .clicked()
+                    {
+                        stop_requested = true;
+                    }
+                });
+            }
             RecorderStatus::Running(process) => {
-                let elapsed = process.started_at.elapsed().as_secs_f32();
+                let elapsed = process.elapsed().as_secs_f32();
+                let can_pause = process.session.can_pause();
                 ui.horizontal(|ui| {
                     ui.colored_label(
                         Color32::from_rgb(255, 235, 140),
                         format!("Recording… {:.1}s elapsed", elapsed),
                     );
+                    if ui
+                        .add_enabled(can_pause, egui::Button::new("Pause"))
+                        .on_hover_text("Finish the current segment; Resume starts a new one and the segments are joined on Stop.")
+                        .on_disabled_hover_text("Adopted recordings cannot be paused.")
+                        .clicked()
+                    {
+                        pause_requested = true;
+                    }
                     if ui
                         .button("Stop (Ctrl+C)")
                         .on_hover_text("Send SIGINT to wf-recorder for a graceful stop.")
@@ -1020,11 +2404,72 @@ impl RecorderApp {
                     }
                 });
             }
+            RecorderStatus::Stopping(_, progress) => {
+                let waited = progress.stage_started.elapsed().as_secs_f32();
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        Color32::from_rgb(255, 235, 140),
+                        format!(
+                            "Finalizing… {:.1}s since {}",
+                            waited,
+                            progress.stage.signal_name()
+                        ),
+                    );
+                    if progress.stage.next().is_some()
+                        && ui
+                            .button("Force stop (SIGKILL)")
+                            .on_hover_text("Kill the process now instead of waiting for it to finish writing the file.")
+                            .clicked()
+                    {
+                        force_requested = true;
+                    }
+                });
+            }
+            RecorderStatus::Paused(session) => {
+                let recorded = session.recorded.as_secs_f32();
+                let restarting = session.restart_at.is_some();
+                ui.horizontal(|ui| {
+                    if restarting {
+                        ui.colored_label(
+                            Color32::from_rgb(255, 235, 140),
+                            format!("Restarting at {:.1}s…", recorded),
+                        );
+                    } else {
+                        ui.colored_label(
+                            Color32::from_rgb(120, 210, 255),
+                            format!("Paused at {:.1}s", recorded),
+                        );
+                    }
+                    if ui
+                        .button(if restarting { "Restart now" } else { "Resume" })
+                        .on_hover_text("Start a new segment with the same wf-recorder arguments.")
+                        .clicked()
+                    {
+                        resume_requested = true;
+                    }
+                    if ui
+                        .button("Stop (Ctrl+C)")
+                        .on_hover_text("Join the recorded segments into the final file.")
+                        .clicked()
+                    {
+                        stop_requested = true;
+                    }
+                });
+            }
         }
 
+        if pause_requested {
+            self.pause_recording();
+        }
+        if resume_requested {
+            self.resume_recording();
+        }
         if start_requested {
             self.start_recording();
         }
+        if start_now_requested {
+            self.start_now();
+        }
         if stop_requested {
             self.stop_recording();
         }
@@ -1045,6 +2490,15 @@ impl RecorderApp {
                         ui.output_mut(|o| o.copied_text = self.log_display.clone());
                     }
                     ui.label("Text is selectable; use Copy all for the full buffer.");
+                    if ui
+                        .small_button("Open logs folder")
+                        .on_hover_text(
+                            "Every session's output is also saved under the XDG state directory.",
+                        )
+                        .clicked()
+                    {
+                        self.open_logs_folder();
+                    }
                 });
                 ui.add_space(4.0);
                 let mut preview = self.log_display.clone();
@@ -1061,10 +2515,13 @@ impl RecorderApp {
     }
 }
 
+// Known options of the encoder get typed widgets; any other key=value pairs
+// stay editable as raw entries below them.
 fn render_param_editor(
     ui: &mut egui::Ui,
     label: &str,
     help: &str,
+    schema: &[ParamSpec],
     params: &mut Vec<ParamEntry>,
     width: f32,
 ) {
@@ -1072,12 +2529,23 @@ fn render_param_editor(
         label_with_help(ui, label, help);
     }
     let control_width = (width * 0.6).max(140.0).min(width);
+    let in_schema = |key: &str| schema.iter().any(|spec| spec.key == key.trim());
     ui.vertical(|ui| {
+        for spec in schema {
+            render_schema_param(ui, spec, params);
+        }
         let mut removal_index: Option<usize> = None;
-        if params.is_empty() {
-            ui.label("No parameters yet.");
+        if params.iter().all(|entry| in_schema(&entry.key)) {
+            ui.label(if schema.is_empty() {
+                "No parameters yet."
+            } else {
+                "No extra parameters."
+            });
         }
         for (idx, entry) in params.iter_mut().enumerate() {
+            if in_schema(&entry.key) {
+                continue;
+            }
             ui.columns(2, |columns| {
                 let left_width = columns[0].available_width().min(control_width);
                 columns[0].add(
@@ -1111,6 +2579,120 @@ fn render_param_editor(
     });
 }
 
+fn render_schema_param(ui: &mut egui::Ui, spec: &ParamSpec, params: &mut Vec<ParamEntry>) {
+    let current = get_param(params, spec.key).map(str::to_string);
+    let mut update = None;
+    ui.horizontal(|ui| {
+        ui.label(spec.label).on_hover_text(spec.help);
+        match spec.kind {
+            ParamKind::Choice(options) => {
+                let selected = current
+                    .clone()
+                    .unwrap_or_else(|| format!("Default ({})", spec.default));
+                egui::ComboBox::from_id_source(("codec_param", spec.key, spec.help))
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        if ui
+                            .selectable_label(
+                                current.is_none(),
+                                format!("Default ({})", spec.default),
+                            )
+                            .clicked()
+                        {
+                            update = Some(None);
+                        }
+                        for option in options {
+                            if ui
+                                .selectable_label(current.as_deref() == Some(*option), *option)
+                                .clicked()
+                            {
+                                update = Some(Some(option.to_string()));
+                            }
+                        }
+                    });
+            }
+            ParamKind::Integer { min, max, .. } => {
+                let mut number = current
+                    .as_deref()
+                    .and_then(|value| spec.parse_number(value))
+                    .or_else(|| spec.parse_number(spec.default))
+                    .unwrap_or(min)
+                    .clamp(min, max);
+                if ui.add(egui::Slider::new(&mut number, min..=max)).changed() {
+                    update = Some(Some(spec.format_number(number)));
+                }
+                if current.is_none() {
+                    ui.weak("default");
+                }
+            }
+        }
+        if current.is_some()
+            && ui
+                .small_button("Reset")
+                .on_hover_text(format!("Remove {} and use the encoder default.", spec.key))
+                .clicked()
+        {
+            update = Some(None);
+        }
+    });
+    if let Some(Err(err)) = current.as_deref().map(|value| spec.validate(value)) {
+        ui.colored_label(Color32::from_rgb(255, 120, 120), err);
+    }
+    if let Some(value) = update {
+        set_param(params, spec.key, value);
+    }
+}
+
+// Curated entries first, then everything else the installed ffmpeg reports.
+fn ffmpeg_picker(
+    ui: &mut egui::Ui,
+    id: &str,
+    value: &mut String,
+    recommended: &[(&str, &str)],
+    detected: &[FfmpegEntry],
+    default_label: Option<&str>,
+    width: f32,
+) {
+    let selected = match recommended.iter().find(|(_, name)| *name == value.as_str()) {
+        Some((label, _)) => (*label).to_string(),
+        None if value.trim().is_empty() => default_label.unwrap_or("Custom").to_string(),
+        None => value.clone(),
+    };
+    egui::ComboBox::from_id_source(id)
+        .width(width)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            if let Some(label) = default_label {
+                ui.selectable_value(value, String::new(), label);
+            }
+            for (label, name) in recommended {
+                let available =
+                    detected.is_empty() || detected.iter().any(|entry| entry.name == *name);
+                let response = ui.selectable_value(value, name.to_string(), *label);
+                if !available {
+                    response.on_hover_text("Not reported by the installed ffmpeg.");
+                }
+            }
+            let others: Vec<&FfmpegEntry> = detected
+                .iter()
+                .filter(|entry| !recommended.iter().any(|(_, name)| *name == entry.name))
+                .collect();
+            if others.is_empty() {
+                return;
+            }
+            if default_label.is_some() || !recommended.is_empty() {
+                ui.separator();
+            }
+            ui.weak("Detected by ffmpeg");
+            for entry in others {
+                let response = ui.selectable_value(value, entry.name.clone(), &entry.name);
+                if !entry.description.is_empty() {
+                    response.on_hover_text(&entry.description);
+                }
+            }
+        });
+}
+
 fn label_with_help(ui: &mut egui::Ui, title: &str, help: &str) -> egui::Response {
     let response = ui.label(RichText::new(title).strong());
     if help.is_empty() {
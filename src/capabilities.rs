@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::paths::cache_dir;
+
+const CACHE_FILE_NAME: &str = "ffmpeg-capabilities.json";
+
+// One line of `ffmpeg -encoders` or `ffmpeg -muxers`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FfmpegEntry {
+    pub name: String,
+    pub description: String,
+}
+
+// What the installed ffmpeg (and so the libav wf-recorder links) can produce.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FfmpegCapabilities {
+    pub version: String,
+    pub video_encoders: Vec<FfmpegEntry>,
+    pub audio_encoders: Vec<FfmpegEntry>,
+    pub muxers: Vec<FfmpegEntry>,
+    pub pixel_formats: Vec<FfmpegEntry>,
+    pub sample_formats: Vec<FfmpegEntry>,
+}
+
+impl FfmpegCapabilities {
+    pub fn is_empty(&self) -> bool {
+        self.video_encoders.is_empty() && self.muxers.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} video and {} audio encoders, {} muxers, {} pixel formats",
+            self.version,
+            self.video_encoders.len(),
+            self.audio_encoders.len(),
+            self.muxers.len(),
+            self.pixel_formats.len()
+        )
+    }
+}
+
+// Runs ffmpeg once per listing; takes a second or so, so call it off the UI thread.
+pub fn detect_ffmpeg_capabilities() -> Result<FfmpegCapabilities, String> {
+    let version = run_ffmpeg_listing("-version")?
+        .lines()
+        .next()
+        .unwrap_or("ffmpeg")
+        .split(" Copyright")
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let (video_encoders, audio_encoders) = parse_encoders(&run_ffmpeg_listing("-encoders")?);
+    Ok(FfmpegCapabilities {
+        version,
+        video_encoders,
+        audio_encoders,
+        muxers: parse_muxers(&run_ffmpeg_listing("-muxers")?),
+        pixel_formats: parse_pixel_formats(&run_ffmpeg_listing("-pix_fmts")?),
+        sample_formats: parse_sample_formats(&run_ffmpeg_listing("-sample_fmts")?),
+    })
+}
+
+//  V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+pub fn parse_encoders(text: &str) -> (Vec<FfmpegEntry>, Vec<FfmpegEntry>) {
+    let mut video = Vec::new();
+    let mut audio = Vec::new();
+    for (flags, entry) in table_rows(text) {
+        match flags.chars().next() {
+            Some('V') => video.push(entry),
+            Some('A') => audio.push(entry),
+            _ => {}
+        }
+    }
+    (video, audio)
+}
+
+//  E  mp4             MP4 (MPEG-4 Part 14)
+pub fn parse_muxers(text: &str) -> Vec<FfmpegEntry> {
+    table_rows(text)
+        .into_iter()
+        .filter(|(flags, _)| flags.contains('E'))
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+// IO... yuv420p                3            12      8-8-8
+// Keeps formats swscale can convert into, the ones worth handing an encoder.
+pub fn parse_pixel_formats(text: &str) -> Vec<FfmpegEntry> {
+    table_rows(text)
+        .into_iter()
+        .filter(|(flags, _)| flags.chars().nth(1) == Some('O'))
+        .map(|(_, entry)| {
+            let bits = entry.description.split_whitespace().nth(1).unwrap_or("?");
+            FfmpegEntry {
+                description: format!("{bits} bits per pixel"),
+                name: entry.name,
+            }
+        })
+        .collect()
+}
+
+// name   depth
+// u8        8
+pub fn parse_sample_formats(text: &str) -> Vec<FfmpegEntry> {
+    text.lines()
+        .skip_while(|line| !line.trim_start().starts_with("name"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?.to_string();
+            let depth = parts.next().unwrap_or("?");
+            Some(FfmpegEntry {
+                name,
+                description: format!("{depth}-bit"),
+            })
+        })
+        .collect()
+}
+
+pub fn cache_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(CACHE_FILE_NAME))
+}
+
+pub fn load_cached_capabilities() -> Option<FfmpegCapabilities> {
+    let contents = fs::read_to_string(cache_path()?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn save_cached_capabilities(capabilities: &FfmpegCapabilities) -> Result<(), String> {
+    let path = cache_path()
+        .ok_or_else(|| "Cannot locate a cache directory (HOME is not set).".to_string())?;
+    save_to(&path, capabilities)
+}
+
+fn save_to(path: &Path, capabilities: &FfmpegCapabilities) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(capabilities)
+        .map_err(|err| format!("Failed to serialize ffmpeg capabilities: {err}"))?;
+    fs::write(path, json).map_err(|err| format!("Failed to write {}: {err}", path.display()))
+}
+
+// Rows after the dashed header rule (its length varies between ffmpeg
+// releases), as (flags, entry).
+fn table_rows(text: &str) -> Vec<(String, FfmpegEntry)> {
+    text.lines()
+        .skip_while(|line| {
+            let line = line.trim();
+            line.len() < 2 || line.chars().any(|char| char != '-')
+        })
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let flags = parts.next()?.to_string();
+            let name = parts.next()?.to_string();
+            let description = parts.collect::<Vec<_>>().join(" ");
+            Some((flags, FfmpegEntry { name, description }))
+        })
+        .collect()
+}
+
+fn run_ffmpeg_listing(flag: &str) -> Result<String, String> {
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", flag])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("Cannot run ffmpeg: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffmpeg {flag} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_encoders, parse_muxers, parse_pixel_formats, parse_sample_formats};
+
+    #[test]
+    fn ffmpeg_listings_are_parsed() {
+        let (video, audio) = parse_encoders(
+            "Encoders:\n V..... = Video\n ------\n V....D libx264              libx264 H.264 / AVC\n V..... h264_vaapi           H.264/AVC (VAAPI) (codec h264)\n A....D aac                  AAC (Advanced Audio Coding)\n S..... srt                  SubRip subtitle\n",
+        );
+        assert_eq!(
+            video
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .collect::<Vec<_>>(),
+            ["libx264", "h264_vaapi"]
+        );
+        assert_eq!(audio[0].description, "AAC (Advanced Audio Coding)");
+
+        let muxers = parse_muxers(
+            "File formats:\n D. = Demuxing supported\n .E = Muxing supported\n --\n  E 3g2             3GP2 (3GPP2 file format)\n D  aac             raw ADTS AAC\n DE matroska        Matroska\n",
+        );
+        assert_eq!(
+            muxers
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .collect::<Vec<_>>(),
+            ["3g2", "matroska"]
+        );
+
+        let pixel_formats = parse_pixel_formats(
+            "Pixel formats:\nFLAGS NAME NB_COMPONENTS BITS_PER_PIXEL BIT_DEPTHS\n-----\nIO... yuv420p                3             12      8-8-8\nI.... bayer_bggr8            3              8      2-4-2\n",
+        );
+        assert_eq!(pixel_formats.len(), 1);
+        assert_eq!(pixel_formats[0].name, "yuv420p");
+        assert_eq!(pixel_formats[0].description, "12 bits per pixel");
+        let sample_formats = parse_sample_formats("name   depth\nu8        8\nfltp     32\n");
+        assert_eq!(sample_formats[1].name, "fltp");
+        assert_eq!(sample_formats[1].description, "32-bit");
+    }
+}
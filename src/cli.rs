@@ -0,0 +1,354 @@
+use crate::config::{CaptureMode, CollisionPolicy};
+use crate::control::ControlRequest;
+
+pub const USAGE: &str = "\
+Usage:
+  wf-recorder-ui                      Launch the graphical interface
+  wf-recorder-ui record [OPTIONS]     Record without a window using saved settings
+  wf-recorder-ui ctl COMMAND          Control an already running UI instance
+  wf-recorder-ui help                 Show this message
+
+Record options:
+  --profile NAME        Use a saved profile instead of the default one
+  --mode MODE           Capture mode: screen, window or area
+  --geometry GEOMETRY   Capture area, e.g. \"0,0 800x600\" (implies --mode area)
+  --output NAME         Screen output to capture (see wf-recorder --list-output)
+  --window QUERY        Window id or title/app substring (implies --mode window)
+  --file TEMPLATE       Override the file template ($timestamp, $date, $time, $format,
+                        $output, $app, $title, $mode, $profile, $hostname, $n, $segment)
+  --format FORMAT       Override the file format / extension
+  --no-audio            Disable audio capture
+  --countdown SECS      Wait before starting wf-recorder
+  --at HH:MM[:SS]       Start at the next occurrence of this local time
+  --max-duration SECS   Stop gracefully after recording this long
+  --max-size MB         Stop gracefully once the file reaches this size
+  --on-exists POLICY    If the file exists: overwrite, suffix or fail (defaults to
+                        the profile's policy, where ask means fail)
+  --standalone          Record here even if a UI instance is running
+  --allow-concurrent    Record even if another session is already recording
+
+The resolved output path is printed on stdout. SIGINT or SIGTERM stops the
+recording gracefully so the file is finalized; if wf-recorder does not exit
+within the profile's stop timeout it is sent SIGTERM and then SIGKILL. When a UI instance is running,
+--profile, --mode and --geometry are forwarded to it instead and the command
+returns once the recording has started there.
+
+Control commands (sent over $XDG_RUNTIME_DIR/wf-recorder-ui/control.sock):
+  start | stop | force-stop | pause | resume | status
+  switch-profile NAME
+  set-capture-mode MODE [--geometry GEOMETRY]
+";
+
+#[derive(Debug, PartialEq)]
+pub enum CliCommand {
+    Gui,
+    Help,
+    Record(RecordOptions),
+    Control(ControlRequest),
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct RecordOptions {
+    pub profile: Option<String>,
+    pub capture_mode: Option<CaptureMode>,
+    pub geometry: Option<String>,
+    pub output: Option<String>,
+    pub window: Option<String>,
+    pub file_template: Option<String>,
+    pub file_format: Option<String>,
+    pub no_audio: bool,
+    pub countdown_secs: Option<u64>,
+    pub start_at: Option<String>,
+    pub max_duration_secs: Option<u64>,
+    pub max_file_size_mb: Option<u64>,
+    pub collision_policy: Option<CollisionPolicy>,
+    pub standalone: bool,
+    pub allow_concurrent: bool,
+}
+
+impl RecordOptions {
+    pub fn unforwardable_options(&self) -> Vec<&'static str> {
+        let mut options = Vec::new();
+        if self.output.is_some() {
+            options.push("--output");
+        }
+        if self.window.is_some() {
+            options.push("--window");
+        }
+        if self.file_template.is_some() {
+            options.push("--file");
+        }
+        if self.file_format.is_some() {
+            options.push("--format");
+        }
+        if self.no_audio {
+            options.push("--no-audio");
+        }
+        if self.countdown_secs.is_some() {
+            options.push("--countdown");
+        }
+        if self.start_at.is_some() {
+            options.push("--at");
+        }
+        if self.max_duration_secs.is_some() {
+            options.push("--max-duration");
+        }
+        if self.max_file_size_mb.is_some() {
+            options.push("--max-size");
+        }
+        if self.collision_policy.is_some() {
+            options.push("--on-exists");
+        }
+        options
+    }
+}
+
+pub fn parse_args<I>(args: I) -> Result<CliCommand, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(CliCommand::Gui);
+    };
+
+    match command.as_str() {
+        "record" => parse_record_options(args).map(CliCommand::Record),
+        "ctl" => parse_control_request(args).map(CliCommand::Control),
+        "help" | "--help" | "-h" => Ok(CliCommand::Help),
+        other => Err(format!("Unknown command `{other}`.")),
+    }
+}
+
+fn parse_record_options<I>(mut args: I) -> Result<RecordOptions, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut options = RecordOptions::default();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| -> Result<String, String> {
+            match inline_value {
+                Some(value) => Ok(value.to_string()),
+                None => args
+                    .next()
+                    .ok_or_else(|| format!("`{name}` expects a value.")),
+            }
+        };
+
+        match flag.as_str() {
+            "--profile" => options.profile = Some(value("--profile")?),
+            "--mode" => options.capture_mode = Some(parse_capture_mode(&value("--mode")?)?),
+            "--geometry" => options.geometry = Some(value("--geometry")?),
+            "--output" => options.output = Some(value("--output")?),
+            "--window" => options.window = Some(value("--window")?),
+            "--file" => options.file_template = Some(value("--file")?),
+            "--format" => options.file_format = Some(value("--format")?),
+            "--no-audio" => options.no_audio = true,
+            "--countdown" => {
+                options.countdown_secs = Some(parse_number("--countdown", &value("--countdown")?)?)
+            }
+            "--at" => options.start_at = Some(value("--at")?),
+            "--max-duration" => {
+                options.max_duration_secs =
+                    Some(parse_number("--max-duration", &value("--max-duration")?)?)
+            }
+            "--max-size" => {
+                options.max_file_size_mb = Some(parse_number("--max-size", &value("--max-size")?)?)
+            }
+            "--on-exists" => {
+                options.collision_policy = Some(parse_collision_policy(&value("--on-exists")?)?)
+            }
+            "--standalone" => options.standalone = true,
+            "--allow-concurrent" => options.allow_concurrent = true,
+            other => return Err(format!("Unknown option `{other}` for `record`.")),
+        }
+    }
+
+    if options.capture_mode.is_none() {
+        if options.geometry.is_some() {
+            options.capture_mode = Some(CaptureMode::Area);
+        } else if options.window.is_some() {
+            options.capture_mode = Some(CaptureMode::Window);
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_control_request<I>(mut args: I) -> Result<ControlRequest, String>
+where
+    I: Iterator<Item = String>,
+{
+    let command = args
+        .next()
+        .ok_or_else(|| "`ctl` expects a command.".to_string())?;
+    let request = match command.as_str() {
+        "start" => ControlRequest::Start,
+        "stop" => ControlRequest::Stop,
+        "force-stop" => ControlRequest::ForceStop,
+        "pause" => ControlRequest::Pause,
+        "resume" => ControlRequest::Resume,
+        "status" => ControlRequest::Status,
+        "switch-profile" => ControlRequest::SwitchProfile {
+            profile: args
+                .next()
+                .ok_or_else(|| "`switch-profile` expects a profile name.".to_string())?,
+        },
+        "set-capture-mode" => {
+            let mode = args
+                .next()
+                .ok_or_else(|| "`set-capture-mode` expects a mode.".to_string())?;
+            let geometry = match args.next().as_deref() {
+                None => None,
+                Some("--geometry") => Some(
+                    args.next()
+                        .ok_or_else(|| "`--geometry` expects a value.".to_string())?,
+                ),
+                Some(other) => match other.strip_prefix("--geometry=") {
+                    Some(value) => Some(value.to_string()),
+                    None => return Err(format!("Unknown option `{other}` for `ctl`.")),
+                },
+            };
+            ControlRequest::SetCaptureMode {
+                mode: parse_capture_mode(&mode)?,
+                geometry,
+            }
+        }
+        other => return Err(format!("Unknown control command `{other}`.")),
+    };
+    if let Some(extra) = args.next() {
+        return Err(format!("Unexpected argument `{extra}`."));
+    }
+    Ok(request)
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("`{flag}` expects a whole number, got `{value}`."))
+}
+
+fn parse_collision_policy(value: &str) -> Result<CollisionPolicy, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "ask" => Ok(CollisionPolicy::Ask),
+        "overwrite" => Ok(CollisionPolicy::Overwrite),
+        "suffix" => Ok(CollisionPolicy::Suffix),
+        "fail" => Ok(CollisionPolicy::Fail),
+        other => Err(format!(
+            "Unknown --on-exists policy `{other}`. Use overwrite, suffix or fail."
+        )),
+    }
+}
+
+pub fn parse_capture_mode(value: &str) -> Result<CaptureMode, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "screen" | "output" => Ok(CaptureMode::Screen),
+        "window" => Ok(CaptureMode::Window),
+        "area" | "region" => Ok(CaptureMode::Area),
+        other => Err(format!(
+            "Unknown capture mode `{other}`. Use screen, window or area."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CliCommand, RecordOptions, parse_args};
+    use crate::config::{CaptureMode, CollisionPolicy};
+    use crate::control::ControlRequest;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_record_with_profile_mode_and_geometry() {
+        let command = parse_args(args(&[
+            "record",
+            "--profile",
+            "demo",
+            "--mode",
+            "area",
+            "--geometry",
+            "0,0 800x600",
+        ]))
+        .expect("arguments should parse");
+
+        assert_eq!(
+            command,
+            CliCommand::Record(RecordOptions {
+                profile: Some("demo".to_string()),
+                capture_mode: Some(CaptureMode::Area),
+                geometry: Some("0,0 800x600".to_string()),
+                ..RecordOptions::default()
+            })
+        );
+    }
+
+    #[test]
+    fn geometry_implies_area_mode_and_inline_values_work() {
+        let Ok(CliCommand::Record(options)) =
+            parse_args(args(&["record", "--geometry=10,10 20x20", "--no-audio"]))
+        else {
+            panic!("expected a record command");
+        };
+        assert_eq!(options.capture_mode, Some(CaptureMode::Area));
+        assert_eq!(options.geometry.as_deref(), Some("10,10 20x20"));
+        assert!(options.no_audio);
+
+        let Ok(CliCommand::Record(options)) = parse_args(args(&[
+            "record",
+            "--countdown=3",
+            "--max-duration",
+            "600",
+            "--at",
+            "21:30",
+            "--on-exists=suffix",
+        ])) else {
+            panic!("expected a record command");
+        };
+        assert_eq!(options.countdown_secs, Some(3));
+        assert_eq!(options.max_duration_secs, Some(600));
+        assert_eq!(options.start_at.as_deref(), Some("21:30"));
+        assert_eq!(options.collision_policy, Some(CollisionPolicy::Suffix));
+        assert_eq!(
+            options.unforwardable_options(),
+            vec!["--countdown", "--at", "--max-duration", "--on-exists"]
+        );
+        assert!(parse_args(args(&["record", "--on-exists", "prompt"])).is_err());
+        assert!(parse_args(args(&["record", "--max-size", "big"])).is_err());
+
+        assert!(parse_args(args(&["record", "--mode"])).is_err());
+        assert!(parse_args(args(&["record", "--bogus"])).is_err());
+        assert_eq!(parse_args(Vec::new()), Ok(CliCommand::Gui));
+    }
+
+    #[test]
+    fn parses_control_commands() {
+        assert_eq!(
+            parse_args(args(&[
+                "ctl",
+                "set-capture-mode",
+                "area",
+                "--geometry",
+                "0,0 8x8"
+            ])),
+            Ok(CliCommand::Control(ControlRequest::SetCaptureMode {
+                mode: CaptureMode::Area,
+                geometry: Some("0,0 8x8".to_string()),
+            }))
+        );
+        assert_eq!(
+            parse_args(args(&["ctl", "switch-profile", "meetings"])),
+            Ok(CliCommand::Control(ControlRequest::SwitchProfile {
+                profile: "meetings".to_string(),
+            }))
+        );
+        assert!(parse_args(args(&["ctl", "start", "now"])).is_err());
+    }
+}
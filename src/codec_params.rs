@@ -0,0 +1,324 @@
+use crate::config::ParamEntry;
+
+const X264_PRESETS: &[&str] = &[
+    "ultrafast",
+    "superfast",
+    "veryfast",
+    "faster",
+    "fast",
+    "medium",
+    "slow",
+    "slower",
+    "veryslow",
+    "placebo",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParamKind {
+    Choice(&'static [&'static str]),
+    // Written as a plain number followed by `suffix`, e.g. `96k`.
+    Integer {
+        min: i64,
+        max: i64,
+        suffix: &'static str,
+    },
+}
+
+// One encoder option wf-recorder passes through `-p`/`-P` as `key=value`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParamSpec {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub help: &'static str,
+    pub kind: ParamKind,
+    // What the encoder uses when the key is not set.
+    pub default: &'static str,
+}
+
+impl ParamSpec {
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match self.kind {
+            ParamKind::Choice(options) if options.contains(&value) => Ok(()),
+            ParamKind::Choice(options) => Err(format!(
+                "{} must be one of {}, not \"{value}\".",
+                self.key,
+                options.join(", ")
+            )),
+            ParamKind::Integer { min, max, .. } => match self.parse_number(value) {
+                Some(number) if (min..=max).contains(&number) => Ok(()),
+                Some(number) => Err(format!(
+                    "{} must be between {min} and {max}, not {number}.",
+                    self.key
+                )),
+                None => Err(format!(
+                    "{} must be a whole number, not \"{value}\".",
+                    self.key
+                )),
+            },
+        }
+    }
+
+    pub fn parse_number(&self, value: &str) -> Option<i64> {
+        let ParamKind::Integer { suffix, .. } = self.kind else {
+            return None;
+        };
+        let value = value.trim();
+        value.strip_suffix(suffix).unwrap_or(value).parse().ok()
+    }
+
+    pub fn format_number(&self, number: i64) -> String {
+        match self.kind {
+            ParamKind::Integer { suffix, .. } => format!("{number}{suffix}"),
+            ParamKind::Choice(_) => number.to_string(),
+        }
+    }
+}
+
+const LIBX264: &[ParamSpec] = &[
+    ParamSpec {
+        key: "preset",
+        label: "Preset",
+        help: "Slower presets compress better at the same quality; screen recording usually wants ultrafast to veryfast.",
+        kind: ParamKind::Choice(X264_PRESETS),
+        default: "medium",
+    },
+    ParamSpec {
+        key: "crf",
+        label: "CRF",
+        help: "Constant quality: lower is better and larger. 18 is visually lossless, 23 the default, 28 small.",
+        kind: ParamKind::Integer {
+            min: 0,
+            max: 51,
+            suffix: "",
+        },
+        default: "23",
+    },
+    ParamSpec {
+        key: "tune",
+        label: "Tune",
+        help: "Adjusts the encoder for the kind of content; stillimage suits slides, zerolatency streaming.",
+        kind: ParamKind::Choice(&[
+            "film",
+            "animation",
+            "grain",
+            "stillimage",
+            "fastdecode",
+            "zerolatency",
+        ]),
+        default: "none",
+    },
+    ParamSpec {
+        key: "profile",
+        label: "Profile",
+        help: "Limits the features used so older players can decode the file.",
+        kind: ParamKind::Choice(&["baseline", "main", "high", "high10", "high422", "high444"]),
+        default: "auto",
+    },
+];
+
+const LIBX265: &[ParamSpec] = &[
+    ParamSpec {
+        key: "preset",
+        label: "Preset",
+        help: "Slower presets compress better at the same quality; x265 is much slower than x264 at each step.",
+        kind: ParamKind::Choice(X264_PRESETS),
+        default: "medium",
+    },
+    ParamSpec {
+        key: "crf",
+        label: "CRF",
+        help: "Constant quality: lower is better and larger. 28 is the default and roughly matches x264 CRF 23.",
+        kind: ParamKind::Integer {
+            min: 0,
+            max: 51,
+            suffix: "",
+        },
+        default: "28",
+    },
+    ParamSpec {
+        key: "tune",
+        label: "Tune",
+        help: "Adjusts the encoder for the kind of content.",
+        kind: ParamKind::Choice(&["grain", "animation", "zerolatency", "fastdecode"]),
+        default: "none",
+    },
+    ParamSpec {
+        key: "profile",
+        label: "Profile",
+        help: "main10 and the 4:4:4 profiles need a matching pixel format.",
+        kind: ParamKind::Choice(&[
+            "main",
+            "main10",
+            "mainstillpicture",
+            "main422-10",
+            "main444-8",
+            "main444-10",
+        ]),
+        default: "auto",
+    },
+];
+
+const LIBVPX_VP9: &[ParamSpec] = &[
+    ParamSpec {
+        key: "crf",
+        label: "CRF",
+        help: "Constant quality: lower is better and larger. 31 suits 1080p; VP9 also needs b=0 for pure constant quality.",
+        kind: ParamKind::Integer {
+            min: 0,
+            max: 63,
+            suffix: "",
+        },
+        default: "32",
+    },
+    ParamSpec {
+        key: "deadline",
+        label: "Deadline",
+        help: "realtime is the only setting fast enough for live screen capture on most machines.",
+        kind: ParamKind::Choice(&["good", "best", "realtime"]),
+        default: "good",
+    },
+    ParamSpec {
+        key: "cpu-used",
+        label: "CPU used",
+        help: "Higher is faster and lower quality; 5 to 8 with the realtime deadline.",
+        kind: ParamKind::Integer {
+            min: -8,
+            max: 8,
+            suffix: "",
+        },
+        default: "1",
+    },
+];
+
+const VAAPI: &[ParamSpec] = &[
+    ParamSpec {
+        key: "qp",
+        label: "QP",
+        help: "Quantizer for constant-quality (CQP) mode: lower is better and larger.",
+        kind: ParamKind::Integer {
+            min: 0,
+            max: 52,
+            suffix: "",
+        },
+        default: "20",
+    },
+    ParamSpec {
+        key: "rc_mode",
+        label: "Rate control",
+        help: "How the GPU spends bits. CQP uses the QP above; CBR/VBR need a bitrate (b=...) as an extra parameter.",
+        kind: ParamKind::Choice(&["auto", "CQP", "CBR", "VBR", "ICQ", "QVBR", "AVBR"]),
+        default: "auto",
+    },
+];
+
+const LIBOPUS: &[ParamSpec] = &[
+    ParamSpec {
+        key: "b",
+        label: "Bitrate",
+        help: "Target bitrate in kbit/s; 64 is fine for speech, 128 for music.",
+        kind: ParamKind::Integer {
+            min: 6,
+            max: 510,
+            suffix: "k",
+        },
+        default: "96k",
+    },
+    ParamSpec {
+        key: "application",
+        label: "Application",
+        help: "voip favours speech intelligibility, lowdelay minimises latency.",
+        kind: ParamKind::Choice(&["voip", "audio", "lowdelay"]),
+        default: "audio",
+    },
+];
+
+// Known options of `encoder`; empty for encoders without a schema.
+pub fn schema_for(encoder: &str) -> &'static [ParamSpec] {
+    match encoder.trim() {
+        "libx264" | "libx264rgb" => LIBX264,
+        "libx265" => LIBX265,
+        "libvpx-vp9" => LIBVPX_VP9,
+        "libopus" => LIBOPUS,
+        encoder if encoder.ends_with("_vaapi") => VAAPI,
+        _ => &[],
+    }
+}
+
+pub fn get_param<'a>(params: &'a [ParamEntry], key: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|entry| entry.key.trim() == key)
+        .map(|entry| entry.value.trim())
+}
+
+// `None` removes the key, so the encoder default applies again.
+pub fn set_param(params: &mut Vec<ParamEntry>, key: &str, value: Option<String>) {
+    let mut value = value;
+    params.retain_mut(|entry| {
+        if entry.key.trim() != key {
+            return true;
+        }
+        match value.take() {
+            Some(value) => {
+                entry.value = value;
+                true
+            }
+            None => false,
+        }
+    });
+    if let Some(value) = value {
+        params.push(ParamEntry {
+            key: key.to_string(),
+            value,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_param, schema_for, set_param};
+    use crate::config::ParamEntry;
+
+    #[test]
+    fn schema_params_round_trip_through_codec_params() {
+        let mut params = vec![
+            ParamEntry {
+                key: "crf".to_string(),
+                value: "30".to_string(),
+            },
+            ParamEntry {
+                key: "x264-params".to_string(),
+                value: "keyint=60".to_string(),
+            },
+        ];
+        set_param(&mut params, "crf", Some("18".to_string()));
+        set_param(&mut params, "preset", Some("veryfast".to_string()));
+        assert_eq!(get_param(&params, "crf"), Some("18"));
+        assert_eq!(params.len(), 3);
+        set_param(&mut params, "crf", None);
+        assert_eq!(get_param(&params, "crf"), None);
+        assert_eq!(get_param(&params, "x264-params"), Some("keyint=60"));
+
+        let x264 = schema_for("libx264");
+        assert!(
+            x264[0]
+                .validate("warp")
+                .unwrap_err()
+                .contains("one of ultrafast")
+        );
+        assert!(
+            x264[1]
+                .validate("60")
+                .unwrap_err()
+                .contains("between 0 and 51")
+        );
+        assert_eq!(x264[1].validate(" 18 "), Ok(()));
+
+        let bitrate = &schema_for("libopus")[0];
+        assert_eq!(bitrate.parse_number("128k"), Some(128));
+        assert_eq!(bitrate.format_number(64), "64k");
+        assert_eq!(schema_for("hevc_vaapi")[0].key, "qp");
+        assert!(schema_for("gif").is_empty());
+    }
+}
@@ -0,0 +1,436 @@
+use crate::codec_params::{get_param, schema_for};
+use crate::config::{AudioMode, RecorderConfig};
+use crate::models::RenderNode;
+use crate::verify::expected_codec_name;
+
+// wf-recorder's own defaults when the fields are left empty.
+const DEFAULT_VIDEO_CODEC: &str = "libx264";
+const DEFAULT_AUDIO_CODEC: &str = "aac";
+const GIF_PIXEL_FORMATS: &[&str] = &["rgb8", "bgr8", "rgb4_byte", "bgr4_byte", "gray", "pal8"];
+const VAAPI_PIXEL_FORMATS: &[&str] = &["nv12", "yuv420p", "vaapi"];
+// From the wf-recorder manpage: VAAPI encoders need their input in GPU memory.
+pub const VAAPI_FILTER: &str = "scale_vaapi=format=nv12:out_range=full";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    // wf-recorder would fail to start or produce an unusable file.
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+// The streams each container can hold, by codec name as ffprobe reports it.
+// Codecs in `*_rejected` are known not to mux and block the start; others
+// missing from `video`/`audio` only warn, since the lists are not complete.
+// `exclusive` containers accept nothing outside their lists, and `None`
+// means the container takes anything.
+struct Container {
+    name: &'static str,
+    exclusive: bool,
+    video: Option<&'static [&'static str]>,
+    video_rejected: &'static [&'static str],
+    audio: Option<&'static [&'static str]>,
+    audio_rejected: &'static [&'static str],
+}
+
+const CONTAINERS: &[Container] = &[
+    Container {
+        name: "mp4",
+        exclusive: false,
+        video: Some(&[
+            "h264",
+            "hevc",
+            "av1",
+            "vp9",
+            "mpeg4",
+            "mpeg2video",
+            "mjpeg",
+            "png",
+        ]),
+        video_rejected: &["gif", "vp8", "ffv1", "theora"],
+        audio: Some(&["aac", "mp3", "opus", "flac", "ac3", "alac"]),
+        audio_rejected: &["vorbis"],
+    },
+    Container {
+        name: "mov",
+        exclusive: false,
+        video: Some(&[
+            "h264", "hevc", "mpeg4", "prores", "mjpeg", "rawvideo", "png", "qtrle", "dnxhd", "ffv1",
+        ]),
+        video_rejected: &["vp8", "vp9"],
+        audio: Some(&["aac", "mp3", "alac", "ac3", "pcm", "flac"]),
+        audio_rejected: &["opus", "vorbis"],
+    },
+    Container {
+        name: "webm",
+        exclusive: true,
+        video: Some(&["vp8", "vp9", "av1"]),
+        video_rejected: &[],
+        audio: Some(&["opus", "vorbis"]),
+        audio_rejected: &[],
+    },
+    Container {
+        name: "gif",
+        exclusive: true,
+        video: Some(&["gif"]),
+        video_rejected: &[],
+        audio: Some(&[]),
+        audio_rejected: &[],
+    },
+    Container {
+        name: "matroska",
+        exclusive: false,
+        video: None,
+        video_rejected: &[],
+        audio: None,
+        audio_rejected: &[],
+    },
+];
+
+impl Container {
+    // `None` when `codec` is listed as fine, otherwise whether it is known
+    // to fail (`Error`) or merely unverified (`Warning`).
+    fn judge(&self, codec: &str, allowed: Option<&[&str]>, rejected: &[&str]) -> Option<Severity> {
+        if rejected.contains(&codec) {
+            return Some(Severity::Error);
+        }
+        match allowed {
+            Some(allowed) if !allowed.contains(&codec) => Some(if self.exclusive {
+                Severity::Error
+            } else {
+                Severity::Warning
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Everything that stops a recording from starting comes first.
+pub fn check_compatibility(config: &RecorderConfig) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let codec = non_empty(&config.codec).unwrap_or(DEFAULT_VIDEO_CODEC);
+    let video = expected_codec_name(codec).unwrap_or_default();
+    let audio_codec = non_empty(&config.audio_codec).unwrap_or(DEFAULT_AUDIO_CODEC);
+    let has_audio = config.audio_mode != AudioMode::None;
+    let container_name = container_name(config);
+    let container = CONTAINERS
+        .iter()
+        .find(|container| container.name == container_name);
+
+    if let Some(container) = container {
+        let label = container_label(container.name);
+        match container.judge(&video, container.video, container.video_rejected) {
+            Some(Severity::Error) => issues.push(Issue::error(format!(
+                "{codec} video cannot be stored in {label}; pick {} or another format.",
+                suggested_container(&video)
+            ))),
+            Some(Severity::Warning) => issues.push(Issue::warning(format!(
+                "{codec} video is not known to work in {label}; MKV accepts almost anything."
+            ))),
+            None => {}
+        }
+        if has_audio && container.audio.is_some_and(<[_]>::is_empty) {
+            issues.push(Issue::error(format!(
+                "{label} cannot carry audio; disable audio or record to MKV."
+            )));
+        } else if has_audio {
+            let audio = audio_codec_name(audio_codec);
+            match container.judge(audio, container.audio, container.audio_rejected) {
+                Some(Severity::Error) => issues.push(Issue::error(format!(
+                    "{audio_codec} audio cannot be stored in {label}; use {}.",
+                    container.audio.unwrap_or_default().join(" or ")
+                ))),
+                Some(Severity::Warning) => issues.push(Issue::warning(format!(
+                    "{audio_codec} audio is not known to work in {label}."
+                ))),
+                None => {}
+            }
+        }
+    }
+    if video == "gif" && has_audio && container.is_none_or(|container| container.name != "gif") {
+        issues.push(Issue::error(
+            "GIF video has no audio; disable audio or pick a video codec.",
+        ));
+    }
+
+    if codec.ends_with("_vaapi") {
+        if config.encoding_device.trim().is_empty() {
+            issues.push(Issue::error(format!(
+                "{codec} needs an encoding device such as /dev/dri/renderD128."
+            )));
+        }
+        if !has_vaapi_filter(&config.filter) {
+            issues.push(Issue::warning(format!(
+                "VAAPI encoders usually need the filter {VAAPI_FILTER}."
+            )));
+        }
+    }
+    if let Some(pixel_format) = non_empty(&config.pixel_format) {
+        issues.extend(pixel_format_issue(
+            codec,
+            &video,
+            pixel_format,
+            get_param(&config.codec_params, "profile"),
+        ));
+    }
+    if video == "gif" && !config.bframes.trim().is_empty() {
+        issues.push(Issue::warning(
+            "GIF has no B-frames; the B-frames setting is ignored.",
+        ));
+    }
+
+    let mut params = vec![(codec, &config.codec_params)];
+    if has_audio {
+        params.push((audio_codec, &config.audio_codec_params));
+    }
+    for (encoder, params) in params {
+        for spec in schema_for(encoder) {
+            if let Some(entry) = params.iter().find(|entry| entry.key.trim() == spec.key)
+                && let Err(err) = spec.validate(&entry.value)
+            {
+                issues.push(Issue::warning(format!("{encoder}: {err}")));
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.severity != Severity::Error);
+    issues
+}
+
+// Fields to change so a VAAPI codec can run; `None` where the current value
+// is fine, and `None` overall for other codecs or a complete setup.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VaapiSetup {
+    pub device: Option<String>,
+    pub filter: Option<String>,
+    pub pixel_format: Option<String>,
+}
+
+pub fn suggest_vaapi_setup(config: &RecorderConfig, nodes: &[RenderNode]) -> Option<VaapiSetup> {
+    if !config.codec.trim().ends_with("_vaapi") {
+        return None;
+    }
+    let setup = VaapiSetup {
+        device: nodes
+            .first()
+            .filter(|_| config.encoding_device.trim().is_empty())
+            .map(|node| node.path.clone()),
+        filter: (!has_vaapi_filter(&config.filter)).then(|| match config.filter.trim() {
+            "" => VAAPI_FILTER.to_string(),
+            filter => format!("{filter},{VAAPI_FILTER}"),
+        }),
+        pixel_format: non_empty(&config.pixel_format)
+            .filter(|format| !VAAPI_PIXEL_FORMATS.contains(format))
+            .map(|_| "yuv420p".to_string()),
+    };
+    (setup != VaapiSetup::default()).then_some(setup)
+}
+
+pub fn first_error(issues: &[Issue]) -> Option<&str> {
+    issues
+        .iter()
+        .find(|issue| issue.severity == Severity::Error)
+        .map(|issue| issue.message.as_str())
+}
+
+fn pixel_format_issue(
+    codec: &str,
+    video: &str,
+    pixel_format: &str,
+    profile: Option<&str>,
+) -> Option<Issue> {
+    if video == "gif" {
+        return (!GIF_PIXEL_FORMATS.contains(&pixel_format)).then(|| {
+            Issue::error(format!(
+                "The GIF encoder cannot take {pixel_format}; use {} or leave the pixel format empty.",
+                GIF_PIXEL_FORMATS.join(", ")
+            ))
+        });
+    }
+    if codec.ends_with("_vaapi") {
+        return (!VAAPI_PIXEL_FORMATS.contains(&pixel_format)).then(|| {
+            Issue::warning(format!(
+                "Most VAAPI drivers only accept {} input, not {pixel_format}.",
+                VAAPI_PIXEL_FORMATS.join(" or ")
+            ))
+        });
+    }
+    // 8-bit 4:2:0 profiles; x264 and x265 refuse anything richer with them.
+    let profile = profile?;
+    let limited = matches!(
+        (video, profile),
+        ("h264", "baseline" | "main" | "high") | ("hevc", "main" | "mainstillpicture")
+    );
+    (limited && !matches!(pixel_format, "yuv420p" | "yuvj420p" | "nv12")).then(|| {
+        Issue::error(format!(
+            "The {profile} profile only supports 8-bit 4:2:0 video; use yuv420p or a different profile for {pixel_format}."
+        ))
+    })
+}
+
+fn has_vaapi_filter(filter: &str) -> bool {
+    filter.contains("scale_vaapi") || filter.contains("hwupload")
+}
+
+// The muxer wins over the file extension, as it does for wf-recorder.
+fn container_name(config: &RecorderConfig) -> String {
+    let name = non_empty(&config.muxer)
+        .or_else(|| non_empty(&config.file_format))
+        .unwrap_or("mp4");
+    muxer_for_extension(name)
+}
+
+// ffmpeg names a few muxers differently from their file extension.
+pub fn muxer_for_extension(extension: &str) -> String {
+    let name = extension.trim().to_ascii_lowercase();
+    match name.as_str() {
+        "mkv" | "mka" => "matroska".to_string(),
+        "m4v" => "mp4".to_string(),
+        "ts" | "m2ts" => "mpegts".to_string(),
+        _ => name,
+    }
+}
+
+fn container_label(name: &str) -> String {
+    match name {
+        "matroska" => "MKV".to_string(),
+        name => name.to_uppercase(),
+    }
+}
+
+fn suggested_container(video: &str) -> &'static str {
+    match video {
+        "vp8" | "vp9" => "WebM",
+        "gif" => "GIF",
+        "h264" | "hevc" => "MP4",
+        _ => "MKV",
+    }
+}
+
+fn audio_codec_name(encoder: &str) -> &str {
+    match encoder {
+        "libopus" => "opus",
+        "libvorbis" => "vorbis",
+        "libmp3lame" => "mp3",
+        "libfdk_aac" => "aac",
+        encoder if encoder.starts_with("pcm_") => "pcm",
+        encoder => encoder,
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    Some(value.trim()).filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Severity, check_compatibility, first_error, muxer_for_extension, suggest_vaapi_setup,
+    };
+    use crate::codec_params::set_param;
+    use crate::config::{AudioMode, RecorderConfig};
+    use crate::models::RenderNode;
+
+    #[test]
+    fn incompatible_codecs_and_containers_block_the_start() {
+        let mut config = RecorderConfig {
+            audio_mode: AudioMode::None,
+            ..RecorderConfig::default()
+        };
+        assert!(check_compatibility(&config).is_empty());
+
+        config.codec = "libvpx-vp9".to_string();
+        config.file_format = "mov".to_string();
+        let issues = check_compatibility(&config);
+        assert!(first_error(&issues).unwrap().contains("pick WebM"));
+
+        config.file_format = "webm".to_string();
+        config.audio_mode = AudioMode::System;
+        config.audio_codec = "aac".to_string();
+        assert!(
+            first_error(&check_compatibility(&config))
+                .unwrap()
+                .contains("use opus or vorbis")
+        );
+        config.audio_codec = "libopus".to_string();
+        assert!(check_compatibility(&config).is_empty());
+
+        config.codec = "gif".to_string();
+        config.file_format = "gif".to_string();
+        config.bframes = "2".to_string();
+        let issues = check_compatibility(&config);
+        assert!(issues[0].message.contains("cannot carry audio"));
+        assert_eq!(issues[1].severity, Severity::Warning);
+
+        config.codec = "h264_vaapi".to_string();
+        config.file_format = "mkv".to_string();
+        config.audio_mode = AudioMode::None;
+        let issues = check_compatibility(&config);
+        assert!(first_error(&issues).unwrap().contains("encoding device"));
+        let nodes = [RenderNode {
+            path: "/dev/dri/renderD128".to_string(),
+            driver: Some("amdgpu".to_string()),
+        }];
+        config.filter = "crop=1920:1080".to_string();
+        let setup = suggest_vaapi_setup(&config, &nodes).expect("setup should be suggested");
+        assert_eq!(setup.device.as_deref(), Some("/dev/dri/renderD128"));
+        assert_eq!(
+            setup.filter.as_deref(),
+            Some("crop=1920:1080,scale_vaapi=format=nv12:out_range=full")
+        );
+        assert_eq!(setup.pixel_format, None);
+        config.encoding_device = "/dev/dri/renderD128".to_string();
+        config.filter = "scale_vaapi=format=nv12:out_range=full".to_string();
+        assert!(check_compatibility(&config).is_empty());
+        assert_eq!(suggest_vaapi_setup(&config, &nodes), None);
+
+        config.codec = "libxvid".to_string();
+        config.file_format = "mov".to_string();
+        let issues = check_compatibility(&config);
+        assert_eq!(first_error(&issues), None);
+        assert!(issues[0].message.contains("not known to work in MOV"));
+        config.codec = "mpeg2video".to_string();
+        config.file_format = "mp4".to_string();
+        assert!(check_compatibility(&config).is_empty());
+
+        config.codec = "libx264".to_string();
+        config.file_format = "mkv".to_string();
+        config.pixel_format = "yuv444p".to_string();
+        assert!(check_compatibility(&config).is_empty());
+        set_param(
+            &mut config.codec_params,
+            "profile",
+            Some("high".to_string()),
+        );
+        assert!(
+            first_error(&check_compatibility(&config))
+                .unwrap()
+                .contains("8-bit 4:2:0")
+        );
+
+        assert_eq!(muxer_for_extension(" MKV"), "matroska");
+        assert_eq!(muxer_for_extension("ts"), "mpegts");
+        assert_eq!(muxer_for_extension("webm"), "webm");
+    }
+}
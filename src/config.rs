@@ -1,15 +1,49 @@
-use chrono::Local;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 use std::env;
-use std::path::PathBuf;
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::detect_hostname;
+use crate::quality::QualityPreset;
+
+pub const TEMPLATE_PLACEHOLDERS: [&str; 12] = [
+    "$timestamp",
+    "$date",
+    "$time",
+    "$format",
+    "$output",
+    "$app",
+    "$title",
+    "$mode",
+    "$profile",
+    "$hostname",
+    "$n",
+    "$segment",
+];
+const MAX_COMPONENT_CHARS: usize = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CaptureMode {
     Screen,
     Window,
     Area,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+impl CaptureMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Screen => "screen",
+            Self::Window => "window",
+            Self::Area => "area",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AudioMode {
     None,
     System,
@@ -17,7 +51,30 @@ pub enum AudioMode {
     Both,
 }
 
-#[derive(Clone)]
+// What to do when the resolved output file already exists and `overwrite` is
+// off; wf-recorder itself would prompt on stdin, which nobody answers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    Ask,
+    Overwrite,
+    Suffix,
+    Fail,
+}
+
+impl CollisionPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ask => "Ask",
+            Self::Overwrite => "Overwrite",
+            Self::Suffix => "Add -1, -2, … suffix",
+            Self::Fail => "Refuse to record",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RecorderConfig {
     pub capture_mode: CaptureMode,
     pub audio_mode: AudioMode,
@@ -30,6 +87,9 @@ pub struct RecorderConfig {
     pub sample_format: String,
     pub codec: String,
     pub codec_params: Vec<ParamEntry>,
+    // While set, the preset owns codec_params, pixel_format, framerate and the
+    // audio bitrate; `None` is manual editing.
+    pub quality_preset: Option<QualityPreset>,
     pub framerate: String,
     pub bframes: String,
     pub buffrate: String,
@@ -41,20 +101,48 @@ pub struct RecorderConfig {
     pub area_geometry: String,
     pub selected_window_id: String,
     pub selected_window_geometry: String,
+    pub selected_window_app: String,
+    pub selected_window_title: String,
     pub selected_speaker_device: String,
     pub selected_microphone_device: String,
     pub file_template: String,
     pub file_format: String,
+    // strftime formats for the $date and $time placeholders.
+    pub date_format: String,
+    pub time_format: String,
     pub no_dmabuf: bool,
     pub no_damage: bool,
     pub log_enabled: bool,
     pub overwrite: bool,
+    pub collision_policy: CollisionPolicy,
+    // Writes `<file>.json` with the argv, config and timing of each recording.
+    pub write_metadata: bool,
+    // Runs ffprobe on finished files and reports missing streams or truncation.
+    pub verify_recordings: bool,
+    // Captures into Matroska, which survives a crash, and remuxes into
+    // `file_format` only after a clean stop.
+    pub record_as_mkv: bool,
+    // Seconds to wait after SIGINT (and again after SIGTERM) before escalating;
+    // wf-recorder can take a while to flush the muxer on long recordings.
+    pub stop_timeout_secs: u64,
+    // Timed recordings; 0 disables each of these.
+    pub countdown_secs: u64,
+    pub max_duration_secs: u64,
+    pub max_file_size_mb: u64,
+    // Rotate to a new file every N minutes and/or megabytes; 0 disables each.
+    pub segment_minutes: u64,
+    pub segment_size_mb: u64,
+    // Opt-in: relaunch wf-recorder into a new segment when it dies on its own,
+    // waiting `restart_backoff_secs` (doubled per attempt) in between.
+    pub restart_on_failure: bool,
+    pub max_restarts: u32,
+    pub restart_backoff_secs: u64,
 }
 
 impl RecorderConfig {
     pub fn build_command_args(
         &self,
-        timestamp_override: Option<String>,
+        context: &TemplateContext,
         screen_geometry_override: Option<String>,
     ) -> Result<(Vec<String>, String), String> {
         let mut args = Vec::new();
@@ -139,6 +227,7 @@ impl RecorderConfig {
         match self.capture_mode {
             CaptureMode::Screen => {
                 push_arg(&mut args, "--output", &self.output);
+                #[allow(clippy::collapsible_if)]
                 if !self.output.trim().is_empty() {
                     if let Some(geometry) = screen_geometry_override {
                         push_arg(&mut args, "--geometry", &geometry);
@@ -168,7 +257,7 @@ impl RecorderConfig {
             }
         }
 
-        let output_file = self.resolve_output_file_with_timestamp(timestamp_override)?;
+        let output_file = self.resolve_output_file(context)?;
         args.push("--file".to_string());
         args.push(output_file.clone());
 
@@ -188,39 +277,112 @@ impl RecorderConfig {
         Ok((args, output_file))
     }
 
-    pub fn preview_output_file(&self) -> Result<String, String> {
-        let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
-        self.resolve_output_file_with_timestamp(Some(timestamp))
+    pub fn preview_output_file(&self, profile: &str) -> Result<String, String> {
+        self.resolve_output_file(&TemplateContext::now(profile))
     }
 
-    fn resolve_output_file_with_timestamp(
-        &self,
-        timestamp_override: Option<String>,
-    ) -> Result<String, String> {
+    pub fn segmenting(&self) -> bool {
+        self.segment_minutes > 0 || self.segment_size_mb > 0
+    }
+
+    fn resolve_output_file(&self, context: &TemplateContext) -> Result<String, String> {
+        Ok(self.first_file(&self.resolve_output_pattern(context)?))
+    }
+
+    pub fn first_file(&self, pattern: &str) -> String {
+        if self.segmenting() {
+            segment_file(pattern, 1)
+        } else {
+            pattern.replace("$segment", &format!("{:03}", 1))
+        }
+    }
+
+    // The output path with everything but `$segment` filled in, so every chunk
+    // of a segmented recording shares the same timestamp and counter.
+    pub fn resolve_output_pattern(&self, context: &TemplateContext) -> Result<String, String> {
         let template = self.file_template.trim();
         if template.is_empty() {
             return Err("Please provide a file template".to_string());
         }
 
-        let timestamp = timestamp_override
-            .unwrap_or_else(|| Local::now().format("%Y-%m-%d_%H-%M-%S").to_string());
         let format = if self.file_format.trim().is_empty() {
-            "mp4".to_string()
+            "mp4"
         } else {
-            self.file_format.trim().to_string()
+            self.file_format.trim()
+        };
+        let date = format_time(&context.time, &self.date_format, "%Y-%m-%d")?;
+        let time = format_time(&context.time, &self.time_format, "%H-%M-%S")?;
+        let output = if self.output.trim().is_empty() {
+            "default"
+        } else {
+            self.output.trim()
         };
 
-        let mut resolved = template.replace("$timestamp", &timestamp);
-        resolved = resolved.replace("$format", &format);
+        let resolved = expand_placeholders(template, |name| {
+            Some(match name {
+                "timestamp" => context.time.format("%Y-%m-%d_%H-%M-%S").to_string(),
+                "date" => date.clone(),
+                "time" => time.clone(),
+                "format" => sanitize_file_component(format),
+                "output" => sanitize_file_component(output),
+                "app" => sanitize_file_component(&self.selected_window_app),
+                "title" => sanitize_file_component(&self.selected_window_title),
+                "mode" => self.capture_mode.as_str().to_string(),
+                "profile" => sanitize_file_component(&context.profile),
+                "hostname" => sanitize_file_component(&context.hostname),
+                "segment" | "n" => format!("${name}"),
+                _ => return None,
+            })
+        })?;
+
+        Ok(self.fill_counter(expand_home(resolved)))
+    }
 
-        if let Some(index) = resolved.find('$') {
-            return Err(format!(
-                "Unknown placeholder starting at position {}. Supported: $timestamp, $format.",
-                index
-            ));
+    // A file recording to `pattern` would replace: any chunk of a segmented
+    // recording, not just the first one.
+    pub fn existing_output(&self, pattern: &str) -> Option<String> {
+        if self.segmenting() {
+            return existing_segment(pattern);
         }
+        let file = self.first_file(pattern);
+        Path::new(&file).exists().then_some(file)
+    }
+
+    // `demo.mp4` becomes `demo-1.mp4`, `demo-2.mp4`, … whichever is free first.
+    pub fn free_output_pattern(&self, pattern: &str) -> String {
+        (1..10_000)
+            .map(|n| insert_before_extension(pattern, &format!("-{n}")))
+            .find(|candidate| self.existing_output(candidate).is_none())
+            .unwrap_or_else(|| insert_before_extension(pattern, "-10000"))
+    }
 
-        Ok(expand_home(resolved))
+    // `$n` becomes the lowest number none of whose files exist yet.
+    fn fill_counter(&self, pattern: String) -> String {
+        if !pattern.contains("$n") {
+            return pattern;
+        }
+        (1..10_000)
+            .map(|n| pattern.replace("$n", &n.to_string()))
+            .find(|candidate| self.existing_output(candidate).is_none())
+            .unwrap_or_else(|| pattern.replace("$n", "10000"))
+    }
+}
+
+// Everything a file template can refer to besides the config itself.
+#[derive(Clone, Debug)]
+pub struct TemplateContext {
+    pub time: DateTime<Local>,
+    pub profile: String,
+    pub hostname: String,
+}
+
+impl TemplateContext {
+    pub fn now(profile: &str) -> Self {
+        Self {
+            time: Local::now(),
+            profile: profile.to_string(),
+            hostname: detect_hostname(),
+        }
     }
 }
 
@@ -238,6 +400,7 @@ impl Default for RecorderConfig {
             sample_format: String::new(),
             codec: "libx264".to_string(),
             codec_params: Vec::new(),
+            quality_preset: None,
             framerate: String::new(),
             bframes: String::new(),
             buffrate: String::new(),
@@ -249,19 +412,37 @@ impl Default for RecorderConfig {
             area_geometry: String::new(),
             selected_window_id: String::new(),
             selected_window_geometry: String::new(),
+            selected_window_app: String::new(),
+            selected_window_title: String::new(),
             selected_speaker_device: String::new(),
             selected_microphone_device: String::new(),
             file_template: "~/Videos/wfrecording/$timestamp.$format".to_string(),
             file_format: "mp4".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            time_format: "%H-%M-%S".to_string(),
             no_dmabuf: false,
             no_damage: false,
             log_enabled: true,
             overwrite: false,
+            collision_policy: CollisionPolicy::Ask,
+            write_metadata: true,
+            verify_recordings: true,
+            record_as_mkv: false,
+            stop_timeout_secs: 10,
+            countdown_secs: 0,
+            max_duration_secs: 0,
+            max_file_size_mb: 0,
+            segment_minutes: 0,
+            segment_size_mb: 0,
+            restart_on_failure: false,
+            max_restarts: 3,
+            restart_backoff_secs: 2,
         }
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ParamEntry {
     pub key: String,
     pub value: String,
@@ -283,6 +464,137 @@ impl ParamEntry {
     }
 }
 
+fn expand_placeholders<F>(template: &str, mut value: F) -> Result<String, String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut resolved = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(index) = rest.find('$') {
+        resolved.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        let name_len = after
+            .find(|c: char| !c.is_ascii_lowercase())
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        match value(name) {
+            Some(expanded) => resolved.push_str(&expanded),
+            None => {
+                return Err(format!(
+                    "Unknown placeholder `${name}` at position {}. Supported: {}.",
+                    template.len() - rest.len() + index,
+                    TEMPLATE_PLACEHOLDERS.join(", ")
+                ));
+            }
+        }
+        rest = &after[name_len..];
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+fn format_time(time: &DateTime<Local>, format: &str, fallback: &str) -> Result<String, String> {
+    let format = if format.trim().is_empty() {
+        fallback
+    } else {
+        format.trim()
+    };
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Invalid date/time format `{format}`."));
+    }
+    Ok(sanitize_file_component(
+        &time.format_with_items(items.into_iter()).to_string(),
+    ))
+}
+
+// Window titles and device names end up in paths: keep letters, digits and a
+// few punctuation marks, squash everything else into single underscores.
+pub fn sanitize_file_component(value: &str) -> String {
+    let mut sanitized = String::with_capacity(value.len());
+    for c in value.trim().chars() {
+        let c = if c.is_alphanumeric() || "-_.+@,=".contains(c) {
+            c
+        } else {
+            '_'
+        };
+        if c == '_' && sanitized.ends_with('_') {
+            continue;
+        }
+        sanitized.push(c);
+    }
+    let sanitized: String = sanitized
+        .trim_matches(|c| c == '_' || c == '.')
+        .chars()
+        .take(MAX_COMPONENT_CHARS)
+        .collect();
+    if sanitized.is_empty() {
+        "unknown".to_string()
+    } else {
+        sanitized
+    }
+}
+
+// Names both rotation chunks and pause segments: `$segment` when the template
+// has it, otherwise the number appended to the file stem.
+pub fn segment_file(pattern: &str, index: usize) -> String {
+    segment_template(pattern).replace("$segment", &format!("{index:03}"))
+}
+
+fn segment_template(pattern: &str) -> String {
+    if pattern.contains("$segment") {
+        pattern.to_string()
+    } else {
+        insert_before_extension(pattern, "-$segment")
+    }
+}
+
+// Any file `segment_file(pattern, n)` names, whatever `n` is. Lists the
+// directory once; a `$segment` outside the file name falls back to probing.
+fn existing_segment(pattern: &str) -> Option<String> {
+    let template = segment_template(pattern);
+    let path = Path::new(&template);
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let only_in_name = !dir.is_some_and(|dir| dir.to_string_lossy().contains("$segment"));
+    let Some((prefix, suffix)) = name
+        .split_once("$segment")
+        .filter(|(_, suffix)| only_in_name && !suffix.contains("$segment"))
+    else {
+        return (1..1_000)
+            .map(|index| segment_file(pattern, index))
+            .find(|file| Path::new(file).exists());
+    };
+    let dir = dir.unwrap_or(Path::new("."));
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .find(|file| {
+            file.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix(suffix))
+                .is_some_and(|number| {
+                    !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit())
+                })
+        })
+        .map(|file| dir.join(file).to_string_lossy().into_owned())
+}
+
+fn insert_before_extension(path: &str, suffix: &str) -> String {
+    let file = Path::new(path);
+    match (file.file_stem(), file.extension()) {
+        (Some(stem), Some(extension)) => file
+            .with_file_name(format!(
+                "{}{suffix}.{}",
+                stem.to_string_lossy(),
+                extension.to_string_lossy()
+            ))
+            .to_string_lossy()
+            .into_owned(),
+        _ => format!("{path}{suffix}"),
+    }
+}
+
 fn push_arg(args: &mut Vec<String>, flag: &str, value: &str) {
     let value = value.trim();
     if !value.is_empty() {
@@ -291,6 +603,7 @@ fn push_arg(args: &mut Vec<String>, flag: &str, value: &str) {
     }
 }
 
+#[allow(clippy::collapsible_if)]
 fn expand_home(path: String) -> String {
     if let Some(rest) = path.strip_prefix("~/") {
         if let Ok(home) = env::var("HOME") {
@@ -308,23 +621,36 @@ fn expand_home(path: String) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{CaptureMode, RecorderConfig};
+    use super::{
+        CaptureMode, RecorderConfig, TemplateContext, sanitize_file_component, segment_file,
+    };
+    use chrono::{Local, TimeZone};
+    use std::{env, fs, process};
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            time: Local
+                .with_ymd_and_hms(2026, 3, 2, 15, 0, 0)
+                .single()
+                .expect("valid local time"),
+            profile: "Demo profile".to_string(),
+            hostname: "box".to_string(),
+        }
+    }
 
     fn index_of(args: &[String], value: &str) -> Option<usize> {
         args.iter().position(|arg| arg == value)
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn screen_mode_includes_geometry_override_when_output_selected() {
         let mut config = RecorderConfig::default();
         config.capture_mode = CaptureMode::Screen;
         config.output = "HDMI-A-1".to_string();
 
         let (args, _) = config
-            .build_command_args(
-                Some("2026-03-02_15-00-00".to_string()),
-                Some("0,0 3840x2160".to_string()),
-            )
+            .build_command_args(&context(), Some("0,0 3840x2160".to_string()))
             .expect("command args should be built");
 
         let output_idx = index_of(&args, "--output").expect("--output should exist");
@@ -335,19 +661,121 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn screen_mode_skips_geometry_override_without_output() {
         let mut config = RecorderConfig::default();
         config.capture_mode = CaptureMode::Screen;
         config.output.clear();
 
         let (args, _) = config
-            .build_command_args(
-                Some("2026-03-02_15-00-00".to_string()),
-                Some("0,0 3840x2160".to_string()),
-            )
+            .build_command_args(&context(), Some("0,0 3840x2160".to_string()))
             .expect("command args should be built");
 
         assert!(index_of(&args, "--output").is_none());
         assert!(index_of(&args, "--geometry").is_none());
     }
+
+    #[test]
+    fn segment_placeholder_numbers_each_chunk() {
+        let config = RecorderConfig {
+            file_template: "/tmp/rec/$timestamp-part$segment.$format".to_string(),
+            segment_minutes: 30,
+            ..RecorderConfig::default()
+        };
+        let (args, output_file) = config
+            .build_command_args(&context(), None)
+            .expect("command args should be built");
+        assert_eq!(output_file, "/tmp/rec/2026-03-02_15-00-00-part001.mp4");
+        assert_eq!(
+            args[index_of(&args, "--file").expect("--file") + 1],
+            output_file
+        );
+
+        let pattern = config
+            .resolve_output_pattern(&context())
+            .expect("pattern should resolve");
+        assert_eq!(
+            segment_file(&pattern, 12),
+            "/tmp/rec/2026-03-02_15-00-00-part012.mp4"
+        );
+        assert_eq!(
+            segment_file("/tmp/rec/demo.mkv", 2),
+            "/tmp/rec/demo-002.mkv"
+        );
+        assert!(
+            RecorderConfig {
+                file_template: "$timestamp.$bogus".to_string(),
+                ..RecorderConfig::default()
+            }
+            .preview_output_file("Default")
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn placeholders_are_filled_and_sanitized() {
+        let config = RecorderConfig {
+            capture_mode: CaptureMode::Window,
+            file_template: "/tmp/$hostname/$profile/$mode-$app-$title-$output_$date@$time.$format"
+                .to_string(),
+            selected_window_app: "org.mozilla.firefox".to_string(),
+            selected_window_title: "Docs / Rust: \"Book\"".to_string(),
+            time_format: "%H:%M".to_string(),
+            ..RecorderConfig::default()
+        };
+        let pattern = config
+            .resolve_output_pattern(&context())
+            .expect("template should resolve");
+        assert_eq!(
+            pattern,
+            "/tmp/box/Demo_profile/window-org.mozilla.firefox-Docs_Rust_Book-default_2026-03-02@15_00.mp4"
+        );
+
+        assert_eq!(sanitize_file_component("  ../..  "), "unknown");
+        let invalid = RecorderConfig {
+            date_format: "%Q".to_string(),
+            file_template: "$date.$format".to_string(),
+            ..RecorderConfig::default()
+        };
+        assert!(invalid.resolve_output_pattern(&context()).is_err());
+    }
+
+    #[test]
+    fn counter_skips_existing_files() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-counter-{}", process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        fs::write(dir.join("take-1.mp4"), b"").expect("file should be written");
+        let config = RecorderConfig {
+            file_template: format!("{}/take-$n.$format", dir.display()),
+            ..RecorderConfig::default()
+        };
+        let (_, output_file) = config
+            .build_command_args(&context(), None)
+            .expect("command args should be built");
+        assert_eq!(output_file, format!("{}/take-2.mp4", dir.display()));
+
+        let pattern = format!("{}/take-1.mp4", dir.display());
+        fs::write(dir.join("take-1-1.mp4"), b"").expect("file should be written");
+        assert_eq!(
+            config.free_output_pattern(&pattern),
+            format!("{}/take-1-2.mp4", dir.display())
+        );
+
+        let segmented = RecorderConfig {
+            segment_minutes: 30,
+            ..config
+        };
+        let pattern = format!("{}/long.mp4", dir.display());
+        assert_eq!(segmented.existing_output(&pattern), None);
+        fs::write(dir.join("long-004.mp4"), b"").expect("file should be written");
+        assert_eq!(
+            segmented.existing_output(&pattern),
+            Some(format!("{}/long-004.mp4", dir.display()))
+        );
+        assert_eq!(
+            segmented.free_output_pattern(&pattern),
+            format!("{}/long-1.mp4", dir.display())
+        );
+        let _ = fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::CaptureMode;
+use crate::paths::runtime_dir;
+
+const SOCKET_FILE_NAME: &str = "control.sock";
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum ControlRequest {
+    Start,
+    Stop,
+    ForceStop,
+    Pause,
+    Resume,
+    Status,
+    SwitchProfile {
+        profile: String,
+    },
+    SetCaptureMode {
+        mode: CaptureMode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        geometry: Option<String>,
+    },
+    Record {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<CaptureMode>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        geometry: Option<String>,
+    },
+    Focus,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ControlStatus>,
+}
+
+impl ControlResponse {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: Some(message.into()),
+            status: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: Some(message.into()),
+            status: None,
+        }
+    }
+
+    pub fn with_status(mut self, status: ControlStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlStatus {
+    pub state: String,
+    pub profile: String,
+    pub capture_mode: Option<CaptureMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_secs: Option<f64>,
+}
+
+pub struct ControlMessage {
+    pub request: ControlRequest,
+    pub reply: Sender<ControlResponse>,
+}
+
+pub struct ControlServer {
+    path: PathBuf,
+}
+
+impl ControlServer {
+    pub fn start<W>(path: &Path, waker: W) -> Result<(Self, Receiver<ControlMessage>), String>
+    where
+        W: Fn() + Send + 'static,
+    {
+        if let Some(parent) = path.parent() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(parent)
+                .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+        }
+        if path.exists() {
+            if instance_running(path) {
+                return Err(format!(
+                    "Another instance is already listening on {}.",
+                    path.display()
+                ));
+            }
+            let _ = fs::remove_file(path);
+        }
+
+        let listener = UnixListener::bind(path)
+            .map_err(|err| format!("Failed to bind control socket {}: {err}", path.display()))?;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let tx = tx.clone();
+                let response = serve_connection(&stream, &tx, &waker);
+                if let Some(response) = response {
+                    let _ = write_message(&stream, &response);
+                }
+            }
+        });
+
+        Ok((
+            Self {
+                path: path.to_path_buf(),
+            },
+            rx,
+        ))
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub fn socket_path() -> PathBuf {
+    runtime_dir().join(SOCKET_FILE_NAME)
+}
+
+pub fn instance_running(path: &Path) -> bool {
+    UnixStream::connect(path).is_ok()
+}
+
+pub fn send_request(path: &Path, request: &ControlRequest) -> Result<ControlResponse, String> {
+    let stream = UnixStream::connect(path).map_err(|err| {
+        format!(
+            "Cannot reach a running wf-recorder UI at {}: {err}",
+            path.display()
+        )
+    })?;
+    let _ = stream.set_read_timeout(Some(REPLY_TIMEOUT));
+    write_message(&stream, request)
+        .map_err(|err| format!("Failed to send control request: {err}"))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .map_err(|err| format!("Failed to read control response: {err}"))?;
+    serde_json::from_str(line.trim())
+        .map_err(|err| format!("Invalid control response {:?}: {err}", line.trim()))
+}
+
+fn serve_connection<W: Fn()>(
+    stream: &UnixStream,
+    tx: &Sender<ControlMessage>,
+    waker: &W,
+) -> Option<ControlResponse> {
+    let _ = stream.set_read_timeout(Some(REPLY_TIMEOUT));
+    let mut line = String::new();
+    if BufReader::new(stream).read_line(&mut line).ok()? == 0 {
+        return None;
+    }
+    let request: ControlRequest = match serde_json::from_str(line.trim()) {
+        Ok(request) => request,
+        Err(err) => return Some(ControlResponse::error(format!("Invalid request: {err}"))),
+    };
+
+    let (reply_tx, reply_rx) = mpsc::channel();
+    if tx
+        .send(ControlMessage {
+            request,
+            reply: reply_tx,
+        })
+        .is_err()
+    {
+        return Some(ControlResponse::error("The UI is shutting down."));
+    }
+    waker();
+    Some(
+        reply_rx
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| ControlResponse::error("The UI did not answer in time.")),
+    )
+}
+
+fn write_message<T: Serialize>(mut stream: &UnixStream, message: &T) -> std::io::Result<()> {
+    let mut json = serde_json::to_string(message).map_err(std::io::Error::other)?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ControlRequest, ControlResponse, ControlServer, send_request};
+    use crate::config::CaptureMode;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn requests_use_kebab_case_command_tags() {
+        let request: ControlRequest = serde_json::from_str(
+            r#"{"command":"set-capture-mode","mode":"area","geometry":"0,0 10x10"}"#,
+        )
+        .expect("request should parse");
+        assert_eq!(
+            request,
+            ControlRequest::SetCaptureMode {
+                mode: CaptureMode::Area,
+                geometry: Some("0,0 10x10".to_string()),
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&ControlRequest::ForceStop).expect("serializes"),
+            r#"{"command":"force-stop"}"#
+        );
+    }
+
+    #[test]
+    fn client_round_trips_through_server() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-control-{}", process::id()));
+        let path = dir.join("control.sock");
+        let (server, rx) = ControlServer::start(&path, || {}).expect("server should start");
+        assert!(ControlServer::start(&path, || {}).is_err());
+
+        std::thread::spawn(move || {
+            for message in rx {
+                let response = match message.request {
+                    ControlRequest::SwitchProfile { profile } => {
+                        ControlResponse::ok(format!("Switched to {profile}"))
+                    }
+                    _ => ControlResponse::error("unexpected"),
+                };
+                let _ = message.reply.send(response);
+            }
+        });
+
+        let response = send_request(
+            &path,
+            &ControlRequest::SwitchProfile {
+                profile: "demo".to_string(),
+            },
+        )
+        .expect("request should succeed");
+        assert!(response.ok);
+        assert_eq!(response.message.as_deref(), Some("Switched to demo"));
+
+        drop(server);
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
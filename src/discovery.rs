@@ -1,6 +1,10 @@
-use crate::models::{AudioDevice, AudioDeviceKind, OutputChoice, WindowChoice};
+use crate::config::{CaptureMode, RecorderConfig};
+use crate::models::{AudioDevice, AudioDeviceKind, OutputChoice, RenderNode, WindowChoice};
 use serde_json::Value;
+use std::path::Path;
 use std::process::Command;
+use std::sync::OnceLock;
+use std::{env, fs};
 
 pub fn detect_outputs() -> Result<Vec<OutputChoice>, String> {
     let output = Command::new("wf-recorder")
@@ -83,6 +87,19 @@ pub fn detect_output_geometry(output_name: &str) -> Result<Option<String>, Strin
     Ok(None)
 }
 
+pub fn detect_screen_geometry_override(config: &RecorderConfig) -> Option<String> {
+    if config.capture_mode != CaptureMode::Screen {
+        return None;
+    }
+    let output = config.output.trim();
+    if output.is_empty() {
+        return None;
+    }
+
+    detect_output_geometry(output).ok().flatten()
+}
+
+#[allow(clippy::needless_match)]
 pub fn detect_audio_devices() -> Result<Vec<AudioDevice>, String> {
     match detect_audio_devices_with_pactl() {
         Ok(devices) => Ok(devices),
@@ -280,6 +297,58 @@ fn classify_audio_device(name: &str, description: &str) -> AudioDeviceKind {
     }
 }
 
+// Render nodes under `<root>/dev/dri`, with the kernel driver bound to each
+// from `<root>/sys/class/drm`. `root` is `/` outside of tests.
+pub fn detect_render_nodes(root: &Path) -> Vec<RenderNode> {
+    let Ok(entries) = fs::read_dir(root.join("dev/dri")) else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<RenderNode> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.starts_with("renderD").then(|| RenderNode {
+                path: entry.path().to_string_lossy().into_owned(),
+                driver: render_node_driver(&root.join("sys/class/drm").join(&name)),
+            })
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.path.cmp(&b.path));
+    nodes
+}
+
+// `device/driver` links to e.g. `../../../bus/pci/drivers/amdgpu`; `uevent`
+// carries the same name as `DRIVER=amdgpu` where the link is missing.
+fn render_node_driver(class_dir: &Path) -> Option<String> {
+    let device = class_dir.join("device");
+    if let Ok(target) = fs::read_link(device.join("driver"))
+        && let Some(name) = target.file_name()
+    {
+        return Some(name.to_string_lossy().into_owned());
+    }
+    fs::read_to_string(device.join("uevent"))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("DRIVER="))
+        .map(|driver| driver.trim().to_string())
+        .filter(|driver| !driver.is_empty())
+}
+
+pub fn detect_hostname() -> String {
+    static HOSTNAME: OnceLock<String> = OnceLock::new();
+    HOSTNAME
+        .get_or_init(|| {
+            fs::read_to_string("/proc/sys/kernel/hostname")
+                .or_else(|_| fs::read_to_string("/etc/hostname"))
+                .ok()
+                .or_else(|| env::var("HOSTNAME").ok())
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "localhost".to_string())
+        })
+        .clone()
+}
+
 pub fn detect_windows() -> Result<Vec<WindowChoice>, String> {
     let mut attempts = Vec::new();
 
@@ -404,6 +473,8 @@ fn collect_sway_windows(node: &Value, out: &mut Vec<WindowChoice>) {
         id,
         label,
         geometry,
+        app: app.to_string(),
+        title: title.to_string(),
     });
 }
 
@@ -429,6 +500,7 @@ fn detect_hypr_windows() -> Result<Vec<WindowChoice>, String> {
 
     let mut windows = Vec::new();
     for client in clients {
+        #[allow(clippy::bool_comparison)]
         if client
             .get("mapped")
             .and_then(Value::as_bool)
@@ -481,8 +553,44 @@ fn detect_hypr_windows() -> Result<Vec<WindowChoice>, String> {
             id,
             label,
             geometry,
+            app: class.to_string(),
+            title: title.to_string(),
         });
     }
 
     Ok(windows)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::detect_render_nodes;
+    use std::os::unix::fs::symlink;
+    use std::{env, fs, process};
+
+    #[test]
+    fn render_nodes_are_read_from_a_fake_sysfs_tree() {
+        let root = env::temp_dir().join(format!("wf-recorder-ui-dri-{}", process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("dev/dri")).expect("dev dir should be created");
+        for name in ["card0", "renderD129", "renderD128"] {
+            fs::write(root.join("dev/dri").join(name), b"").expect("node should be written");
+        }
+        let amd = root.join("sys/class/drm/renderD128/device");
+        fs::create_dir_all(&amd).expect("sysfs dir should be created");
+        symlink("../../../bus/pci/drivers/amdgpu", amd.join("driver"))
+            .expect("driver link should be created");
+        let intel = root.join("sys/class/drm/renderD129/device");
+        fs::create_dir_all(&intel).expect("sysfs dir should be created");
+        fs::write(intel.join("uevent"), "DRIVER=i915\nPCI_ID=8086:46A6\n")
+            .expect("uevent should be written");
+
+        let nodes = detect_render_nodes(&root);
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes[0].path.ends_with("dev/dri/renderD128"));
+        assert_eq!(nodes[0].driver.as_deref(), Some("amdgpu"));
+        assert_eq!(nodes[1].driver.as_deref(), Some("i915"));
+        assert!(nodes[1].label().ends_with("renderD129 (i915)"));
+        assert!(detect_render_nodes(&root.join("missing")).is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
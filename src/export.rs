@@ -0,0 +1,319 @@
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{self, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::recorder::format_exit_status;
+use crate::trim::derived_path;
+
+const AUDIO_KBPS: u32 = 128;
+const MIN_VIDEO_KBPS: f64 = 100.0;
+// Headroom for container overhead and rate-control overshoot.
+const SIZE_MARGIN: f64 = 0.96;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportPreset {
+    TargetSize,
+    Web720p,
+    Web1080p,
+    LosslessArchive,
+}
+
+impl ExportPreset {
+    pub const ALL: [ExportPreset; 4] = [
+        ExportPreset::TargetSize,
+        ExportPreset::Web720p,
+        ExportPreset::Web1080p,
+        ExportPreset::LosslessArchive,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportPreset::TargetSize => "Fit a file size",
+            ExportPreset::Web720p => "Web 720p",
+            ExportPreset::Web1080p => "Web 1080p",
+            ExportPreset::LosslessArchive => "Lossless archive",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ExportPreset::TargetSize => {
+                "Two-pass H.264/AAC MP4 whose bitrate is computed so the file stays under the size cap."
+            }
+            ExportPreset::Web720p => "H.264/AAC MP4 scaled down to 720p lines, CRF 23.",
+            ExportPreset::Web1080p => "H.264/AAC MP4 scaled down to 1080p lines, CRF 21.",
+            ExportPreset::LosslessArchive => {
+                "FFV1 video and FLAC audio in Matroska; large, but bit-exact."
+            }
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportPreset::LosslessArchive => "mkv",
+            _ => "mp4",
+        }
+    }
+
+    pub fn file_label(self) -> &'static str {
+        match self {
+            ExportPreset::TargetSize => "compressed",
+            ExportPreset::Web720p => "720p",
+            ExportPreset::Web1080p => "1080p",
+            ExportPreset::LosslessArchive => "archive",
+        }
+    }
+}
+
+// The ffmpeg invocations for one export, run in order.
+#[derive(Debug)]
+pub struct ExportPlan {
+    pub output: String,
+    pub passes: Vec<Vec<String>>,
+    passlog: Option<String>,
+}
+
+pub fn export_output(source: &str, preset: ExportPreset) -> String {
+    derived_path(source, preset.file_label(), Some(preset.extension()))
+}
+
+// Leaves `1 - SIZE_MARGIN` of the budget unused so the cap holds.
+pub fn video_bitrate_kbps(target_mb: f64, duration: f64, audio_kbps: u32) -> Result<u32, String> {
+    if duration <= 0.0 {
+        return Err("Cannot size an export without knowing the recording's duration.".to_string());
+    }
+    let total_kbps = target_mb * 8_000.0 / duration * SIZE_MARGIN;
+    let video_kbps = total_kbps - f64::from(audio_kbps);
+    if video_kbps < MIN_VIDEO_KBPS {
+        let minimum = (MIN_VIDEO_KBPS + f64::from(audio_kbps)) * duration / 8_000.0 / SIZE_MARGIN;
+        return Err(format!(
+            "{target_mb} MB is too small for {duration:.0} seconds of video; allow at least {:.1} MB.",
+            minimum.ceil()
+        ));
+    }
+    Ok(video_kbps.round() as u32)
+}
+
+pub fn plan_export(
+    source: &str,
+    output: &str,
+    preset: ExportPreset,
+    target_mb: f64,
+    duration: f64,
+    has_audio: bool,
+) -> Result<ExportPlan, String> {
+    let strings = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let input = strings(&["-i", source]);
+    let web = |height: u32, crf: &str| {
+        let mut args = input.clone();
+        args.extend(strings(&["-vf"]));
+        args.push(format!("scale=-2:'min({height},ih)'"));
+        args.extend(strings(&[
+            "-c:v",
+            "libx264",
+            "-preset",
+            "medium",
+            "-crf",
+            crf,
+            "-pix_fmt",
+            "yuv420p",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "128k",
+            "-movflags",
+            "+faststart",
+            output,
+        ]));
+        args
+    };
+    let (passes, passlog) = match preset {
+        ExportPreset::Web720p => (vec![web(720, "23")], None),
+        ExportPreset::Web1080p => (vec![web(1080, "21")], None),
+        ExportPreset::LosslessArchive => {
+            let mut args = input;
+            args.extend(strings(&[
+                "-map", "0", "-c:v", "ffv1", "-level", "3", "-c:a", "flac", output,
+            ]));
+            (vec![args], None)
+        }
+        ExportPreset::TargetSize => {
+            let audio_kbps = if has_audio { AUDIO_KBPS } else { 0 };
+            let bitrate = format!("{}k", video_bitrate_kbps(target_mb, duration, audio_kbps)?);
+            let passlog = passlog_prefix();
+            let video = |pass: &str| {
+                let mut args = input.clone();
+                args.extend(strings(&[
+                    "-c:v",
+                    "libx264",
+                    "-preset",
+                    "medium",
+                    "-b:v",
+                    &bitrate,
+                    "-pix_fmt",
+                    "yuv420p",
+                    "-pass",
+                    pass,
+                    "-passlogfile",
+                    &passlog,
+                ]));
+                args
+            };
+            let mut first = video("1");
+            first.extend(strings(&["-an", "-f", "null", "/dev/null"]));
+            let mut second = video("2");
+            second.extend(strings(&["-c:a", "aac", "-b:a"]));
+            second.push(format!("{AUDIO_KBPS}k"));
+            second.extend(strings(&["-movflags", "+faststart", output]));
+            (vec![first, second], Some(passlog))
+        }
+    };
+    Ok(ExportPlan {
+        output: output.to_string(),
+        passes,
+        passlog,
+    })
+}
+
+// Runs every pass, reporting overall progress in 0.0..=1.0. Returns the size
+// of the finished file.
+pub fn run_export(
+    plan: &ExportPlan,
+    duration: f64,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(f32),
+) -> Result<u64, String> {
+    let passes = plan.passes.len();
+    let mut result = Ok(());
+    for (index, args) in plan.passes.iter().enumerate() {
+        result = run_pass(args, cancel, |seconds| {
+            let fraction = if duration > 0.0 {
+                (seconds / duration).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            on_progress(((index as f64 + fraction) / passes as f64) as f32);
+        });
+        if result.is_err() {
+            break;
+        }
+    }
+    if let Some(passlog) = &plan.passlog {
+        let _ = fs::remove_file(format!("{passlog}-0.log"));
+        let _ = fs::remove_file(format!("{passlog}-0.log.mbtree"));
+    }
+    if let Err(err) = result {
+        let _ = fs::remove_file(&plan.output);
+        return Err(err);
+    }
+    fs::metadata(&plan.output)
+        .map(|metadata| metadata.len())
+        .map_err(|err| format!("ffmpeg finished but {} is unreadable: {err}", plan.output))
+}
+
+// `out_time_us=12500000` → 12.5 seconds encoded so far.
+pub fn parse_progress_line(line: &str) -> Option<f64> {
+    let (key, value) = line.trim().split_once('=')?;
+    match key {
+        // Despite its name, out_time_ms is also in microseconds.
+        "out_time_us" | "out_time_ms" => {
+            value.parse::<f64>().ok().map(|micros| micros / 1_000_000.0)
+        }
+        _ => None,
+    }
+}
+
+fn run_pass(
+    args: &[String],
+    cancel: &AtomicBool,
+    mut on_seconds: impl FnMut(f64),
+) -> Result<(), String> {
+    let mut child = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-nostats", "-y"])
+        .args(["-progress", "pipe:1"])
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Cannot run ffmpeg: {err}"))?;
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if cancel.load(Ordering::Relaxed) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("Export cancelled.".to_string());
+            }
+            if let Some(seconds) = parse_progress_line(&line) {
+                on_seconds(seconds);
+            }
+        }
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    let status = child
+        .wait()
+        .map_err(|err| format!("Failed to wait for ffmpeg: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "ffmpeg failed{}: {}",
+            format_exit_status(status),
+            stderr.trim()
+        ))
+    }
+}
+
+// Unique per export so concurrent two-pass runs never share a stats file.
+fn passlog_prefix() -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    env::temp_dir()
+        .join(format!(
+            "wf-recorder-ui-2pass-{}-{}",
+            process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportPreset, parse_progress_line, plan_export, video_bitrate_kbps};
+
+    #[test]
+    fn size_targets_become_two_pass_bitrates() {
+        assert_eq!(video_bitrate_kbps(10.0, 60.0, 128), Ok(1152));
+        assert!(
+            video_bitrate_kbps(1.0, 600.0, 128)
+                .unwrap_err()
+                .contains("at least 18")
+        );
+
+        let plan = plan_export(
+            "/v/demo.mkv",
+            "/v/demo.compressed.mp4",
+            ExportPreset::TargetSize,
+            10.0,
+            60.0,
+            false,
+        )
+        .expect("plan should build");
+        assert_eq!(plan.passes.len(), 2);
+        assert!(plan.passes[0].join(" ").contains("-b:v 1280k"));
+        assert!(plan.passes[0].ends_with(&["/dev/null".to_string()]));
+        assert_eq!(
+            plan.passes[1].last().map(String::as_str),
+            Some("/v/demo.compressed.mp4")
+        );
+
+        assert_eq!(parse_progress_line("out_time_us=12500000"), Some(12.5));
+        assert_eq!(parse_progress_line("out_time_us=N/A"), None);
+        assert_eq!(parse_progress_line("progress=continue"), None);
+    }
+}
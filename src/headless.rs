@@ -0,0 +1,462 @@
+use chrono::Local;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+
+use crate::cli::RecordOptions;
+use crate::compat::{check_compatibility, first_error};
+use crate::config::{
+    AudioMode, CaptureMode, CollisionPolicy, RecorderConfig, TemplateContext, segment_file,
+};
+use crate::control::{ControlRequest, instance_running, send_request, socket_path};
+use crate::discovery::{detect_screen_geometry_override, detect_windows};
+use crate::instance::{RecorderPidFile, RecorderPidRecord, SessionLock};
+use crate::quality::apply_quality_preset;
+use crate::recorder::{
+    RestartPolicy, StopProgress, StopStage, format_exit_status, send_stop_signal, stop_with_parent,
+    with_output_file,
+};
+use crate::recovery::{intermediate_path, remux};
+use crate::schedule::{RecordingLimits, delay_until, format_duration};
+use crate::settings::load_settings;
+use crate::sidecar::RecordingMetadata;
+use crate::verify::{Expectations, verify_recording};
+
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+pub fn run_record(options: RecordOptions) -> i32 {
+    match record(options) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("wf-recorder-ui: {err}");
+            1
+        }
+    }
+}
+
+fn record(options: RecordOptions) -> Result<i32, String> {
+    let socket = socket_path();
+    if !options.standalone && instance_running(&socket) {
+        return forward_to_instance(&socket, &options);
+    }
+
+    let settings = load_settings()?;
+    let profile_name = options
+        .profile
+        .clone()
+        .unwrap_or_else(|| settings.profiles.default_profile.clone());
+    let mut config = settings
+        .profiles
+        .get(&profile_name)
+        .map(|profile| profile.config.clone())
+        .ok_or_else(|| {
+            format!(
+                "No profile named \"{profile_name}\". Available: {}.",
+                settings.profiles.names().join(", ")
+            )
+        })?;
+    apply_overrides(&mut config, &options)?;
+    apply_quality_preset(&mut config);
+    let issues = check_compatibility(&config);
+    if let Some(err) = first_error(&issues) {
+        return Err(err.to_string());
+    }
+    for issue in &issues {
+        eprintln!("wf-recorder-ui: warning: {}", issue.message);
+    }
+
+    let screen_geometry_override = detect_screen_geometry_override(&config);
+    let mut context = TemplateContext::now(&profile_name);
+    let (mut args, mut output_file) =
+        config.build_command_args(&context, screen_geometry_override.clone())?;
+    let delay = match &options.start_at {
+        Some(time) => delay_until(time, Local::now().naive_local())?,
+        None => Duration::from_secs(config.countdown_secs),
+    };
+
+    let _session_lock = if options.allow_concurrent {
+        SessionLock::acquire().ok()
+    } else {
+        Some(
+            SessionLock::acquire()
+                .map_err(|err| format!("{err} Pass --allow-concurrent to record anyway."))?,
+        )
+    };
+
+    install_stop_handlers()?;
+
+    if !delay.is_zero() {
+        eprintln!(
+            "wf-recorder-ui: recording starts in {}.",
+            format_duration(delay)
+        );
+        if !wait_unless_stopped(delay) {
+            return Err("Cancelled before the recording started.".to_string());
+        }
+        // Resolve again so $timestamp reflects when recording actually began.
+        context.time = Local::now();
+        (args, output_file) = config.build_command_args(&context, screen_geometry_override)?;
+    }
+
+    let mut pattern = config.resolve_output_pattern(&context)?;
+    if !config.overwrite
+        && let Some(existing) = config.existing_output(&pattern)
+    {
+        match config.collision_policy {
+            CollisionPolicy::Ask | CollisionPolicy::Fail => {
+                return Err(format!(
+                    "{existing} already exists. Pass --on-exists overwrite or --on-exists suffix to record anyway."
+                ));
+            }
+            CollisionPolicy::Overwrite => args.push("--overwrite".to_string()),
+            CollisionPolicy::Suffix => {
+                pattern = config.free_output_pattern(&pattern);
+                output_file = config.first_file(&pattern);
+            }
+        }
+    }
+
+    if let Some(parent) = Path::new(&output_file).parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            format!(
+                "Failed to create output directory {}: {err}",
+                parent.display()
+            )
+        })?;
+    }
+
+    let chunk_limits = if config.segmenting() {
+        RecordingLimits::for_segments(&config)
+    } else {
+        RecordingLimits::default()
+    };
+    let limits = RecordingLimits::from_config(&config);
+    let restart_policy = RestartPolicy::from_config(&config);
+    let stop_timeout = Duration::from_secs(config.stop_timeout_secs);
+    let started_at = Instant::now();
+    let mut chunks: Vec<String> = Vec::new();
+    let mut chunk_durations: Vec<Duration> = Vec::new();
+    let mut chunk_file = output_file;
+    let mut file_index = 1;
+    let mut restarts = 0;
+    let mut attempt = 0;
+    let mut auto_stop_reason: Option<String> = None;
+    // Same rule as the GUI: MKV-first only for single-file recordings.
+    let record_as_mkv =
+        config.record_as_mkv && !config.segmenting() && config.muxer.trim().is_empty();
+
+    let (status, stop_progress) = loop {
+        let capture_file = record_as_mkv
+            .then(|| intermediate_path(&chunk_file))
+            .flatten()
+            .unwrap_or_else(|| chunk_file.clone());
+        let mut child = spawn_recorder(&with_output_file(&args, &capture_file))?;
+        let _pid_file = RecorderPidFile::create(&RecorderPidRecord::new(child.id(), &capture_file))
+            .map_err(|err| eprintln!("wf-recorder-ui: {err}"))
+            .ok();
+
+        println!("{chunk_file}");
+        let _ = io::stdout().flush();
+
+        let chunk_started = Instant::now();
+        let chunk_started_at = Local::now();
+        let mut rotate = false;
+        let mut stop_progress: Option<StopProgress> = None;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) => {}
+                Err(err) => return Err(format!("Failed to poll wf-recorder status: {err}")),
+            }
+            if stop_progress.is_none() && !(limits.is_unlimited() && chunk_limits.is_unlimited()) {
+                let chunk_bytes = file_size(&chunk_file);
+                let bytes = chunk_bytes + chunks.iter().map(|chunk| file_size(chunk)).sum::<u64>();
+                auto_stop_reason = limits.exceeded(started_at.elapsed(), bytes);
+                rotate = chunk_limits
+                    .exceeded(chunk_started.elapsed(), chunk_bytes)
+                    .is_some();
+            }
+            match &mut stop_progress {
+                None if STOP_REQUESTED.load(Ordering::SeqCst)
+                    || auto_stop_reason.is_some()
+                    || rotate =>
+                {
+                    if let Err(err) = send_stop_signal(child.id(), StopStage::Interrupt) {
+                        eprintln!("wf-recorder-ui: failed to signal wf-recorder: {err}");
+                    }
+                    stop_progress = Some(StopProgress::new(StopStage::Interrupt, stop_timeout));
+                }
+                Some(progress) => match progress.escalate(child.id()) {
+                    Ok(true) => eprintln!(
+                        "wf-recorder-ui: wf-recorder is still running; sent {}.",
+                        progress.stage.signal_name()
+                    ),
+                    Ok(false) => {}
+                    Err(err) => eprintln!("wf-recorder-ui: failed to signal wf-recorder: {err}"),
+                },
+                None => {}
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
+
+        let mut recorded_file = chunk_file.clone();
+        if capture_file != chunk_file && Path::new(&capture_file).exists() {
+            match remux(&capture_file, &chunk_file) {
+                Ok(()) => {
+                    let _ = fs::remove_file(&capture_file);
+                }
+                Err(err) => {
+                    eprintln!("wf-recorder-ui: {err}; kept the Matroska recording.");
+                    recorded_file = capture_file;
+                }
+            }
+        }
+        let chunk_exists = Path::new(&recorded_file).exists();
+        if chunk_exists {
+            if config.write_metadata {
+                let metadata = RecordingMetadata::new(
+                    &recorded_file,
+                    &args,
+                    &config,
+                    &profile_name,
+                    chunk_started_at,
+                    chunk_started.elapsed(),
+                    format_exit_status(status).trim_start_matches(", "),
+                );
+                if let Err(err) = metadata.write() {
+                    eprintln!("wf-recorder-ui: {err}");
+                }
+            }
+            chunks.push(recorded_file);
+            chunk_durations.push(chunk_started.elapsed());
+        }
+        if rotate
+            && chunk_exists
+            && auto_stop_reason.is_none()
+            && !STOP_REQUESTED.load(Ordering::SeqCst)
+        {
+            eprintln!(
+                "wf-recorder-ui: chunk {} finished; continuing in a new file.",
+                chunks.len()
+            );
+            file_index += 1;
+            chunk_file = segment_file(&pattern, file_index);
+            continue;
+        }
+        let ran = chunk_started.elapsed();
+        if stop_progress.is_none()
+            && !status.success()
+            && let Some(policy) = &restart_policy
+            && let Some(delay) = policy.delay_for(policy.next_attempt(attempt, ran))
+        {
+            attempt = policy.next_attempt(attempt, ran);
+            restarts += 1;
+            eprintln!(
+                "wf-recorder-ui: wf-recorder exited{}; restarting in {} (attempt {attempt} of {}).",
+                format_exit_status(status),
+                format_duration(delay),
+                policy.max_restarts
+            );
+            if wait_unless_stopped(delay) {
+                file_index += 1;
+                chunk_file = segment_file(&pattern, file_index);
+                continue;
+            }
+        }
+        break (status, stop_progress);
+    };
+
+    eprintln!(
+        "wf-recorder exited after {:.1} seconds{}",
+        started_at.elapsed().as_secs_f32(),
+        format_exit_status(status)
+    );
+    if chunks.len() > 1 {
+        eprintln!("Recorded {} chunks:", chunks.len());
+        for chunk in &chunks {
+            eprintln!("  {chunk}");
+        }
+    }
+    if restarts > 0 {
+        eprintln!("Restarted wf-recorder {restarts} time(s) after unexpected exits.");
+    }
+    if let Some(reason) = &auto_stop_reason {
+        eprintln!("{reason}");
+    }
+    if let Some(progress) = &stop_progress {
+        eprintln!("{}", progress.outcome());
+    }
+    if config.verify_recordings {
+        for (chunk, duration) in chunks.iter().zip(&chunk_durations) {
+            let expectations = Expectations::from_config(&config, *duration);
+            for warning in verify_recording(chunk, &expectations) {
+                eprintln!("wf-recorder-ui: {chunk}: {warning}");
+            }
+        }
+    }
+    if chunks.is_empty() {
+        return Err(format!("Recording did not produce {chunk_file}."));
+    }
+
+    Ok(if status.success() {
+        0
+    } else {
+        status.code().unwrap_or(1)
+    })
+}
+
+// Own process group: terminal Ctrl+C reaches only us, and we forward a single SIGINT.
+fn spawn_recorder(args: &[String]) -> Result<Child, String> {
+    let mut command = Command::new("wf-recorder");
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(io::stderr()))
+        .stderr(Stdio::inherit());
+    #[cfg(unix)]
+    command.process_group(0);
+    stop_with_parent(&mut command);
+    command
+        .spawn()
+        .map_err(|err| format!("Failed to start wf-recorder: {err}"))
+}
+
+// Returns false if a stop signal arrived while waiting.
+fn wait_unless_stopped(delay: Duration) -> bool {
+    let until = Instant::now() + delay;
+    while Instant::now() < until {
+        if STOP_REQUESTED.load(Ordering::SeqCst) {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    !STOP_REQUESTED.load(Ordering::SeqCst)
+}
+
+fn file_size(path: &str) -> u64 {
+    fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+fn forward_to_instance(socket: &Path, options: &RecordOptions) -> Result<i32, String> {
+    let unforwardable = options.unforwardable_options();
+    if !unforwardable.is_empty() {
+        return Err(format!(
+            "A wf-recorder UI is running and cannot take {}. Pass --standalone to record here instead.",
+            unforwardable.join(", ")
+        ));
+    }
+
+    let response = send_request(
+        socket,
+        &ControlRequest::Record {
+            profile: options.profile.clone(),
+            mode: options.capture_mode,
+            geometry: options.geometry.clone(),
+        },
+    )?;
+    if !response.ok {
+        return Err(response
+            .message
+            .unwrap_or_else(|| "The running UI refused to start recording.".to_string()));
+    }
+    match response.status.and_then(|status| status.output_file) {
+        Some(output_file) => println!("{output_file}"),
+        None => eprintln!(
+            "{}",
+            response
+                .message
+                .unwrap_or_else(|| "Recording started in the running UI.".to_string())
+        ),
+    }
+    Ok(0)
+}
+
+fn apply_overrides(config: &mut RecorderConfig, options: &RecordOptions) -> Result<(), String> {
+    if let Some(mode) = options.capture_mode {
+        config.capture_mode = mode;
+    }
+    if let Some(geometry) = &options.geometry {
+        config.area_geometry = geometry.clone();
+    }
+    if let Some(output) = &options.output {
+        config.output = output.clone();
+    }
+    if let Some(template) = &options.file_template {
+        config.file_template = template.clone();
+    }
+    if let Some(format) = &options.file_format {
+        config.file_format = format.clone();
+    }
+    if options.no_audio {
+        config.audio_mode = AudioMode::None;
+        config.audio_enabled = false;
+    }
+    if let Some(secs) = options.countdown_secs {
+        config.countdown_secs = secs;
+    }
+    if let Some(secs) = options.max_duration_secs {
+        config.max_duration_secs = secs;
+    }
+    if let Some(size) = options.max_file_size_mb {
+        config.max_file_size_mb = size;
+    }
+    if let Some(policy) = options.collision_policy {
+        config.collision_policy = policy;
+    }
+
+    if config.capture_mode == CaptureMode::Window
+        && let Some(query) = &options.window
+    {
+        let windows = detect_windows()?;
+        let needle = query.to_lowercase();
+        let window = windows
+            .iter()
+            .find(|window| window.id == *query)
+            .or_else(|| {
+                windows
+                    .iter()
+                    .find(|window| window.label.to_lowercase().contains(&needle))
+            })
+            .ok_or_else(|| format!("No window matches \"{query}\"."))?;
+        config.selected_window_id = window.id.clone();
+        config.selected_window_geometry = window.geometry.clone();
+        config.selected_window_app = window.app.clone();
+        config.selected_window_title = window.title.clone();
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+extern "C" fn handle_stop_signal(_signal: libc::c_int) {
+    STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+fn install_stop_handlers() -> Result<(), String> {
+    let handler = handle_stop_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        let previous = unsafe { libc::signal(signal, handler) };
+        if previous == libc::SIG_ERR {
+            return Err(format!(
+                "Failed to install signal handler: {}",
+                io::Error::last_os_error()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn install_stop_handlers() -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,329 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{CaptureMode, RecorderConfig};
+use crate::paths::state_dir;
+use crate::sidecar::sidecar_path;
+
+pub const HISTORY_VERSION: u64 = 1;
+const HISTORY_FILE_NAME: &str = "history.json";
+const MAX_HISTORY_ENTRIES: usize = 500;
+
+// How a recording was captured; adopted strays leave these empty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureDetails {
+    pub profile: String,
+    pub capture_mode: String,
+    pub geometry: String,
+    pub codec: String,
+}
+
+impl CaptureDetails {
+    pub fn from_config(config: &RecorderConfig, profile: &str) -> Self {
+        let geometry = match config.capture_mode {
+            CaptureMode::Screen => config.output.trim(),
+            CaptureMode::Window => config.selected_window_geometry.trim(),
+            CaptureMode::Area => config.area_geometry.trim(),
+        };
+        Self {
+            profile: profile.to_string(),
+            capture_mode: config.capture_mode.as_str().to_string(),
+            geometry: geometry.to_string(),
+            codec: config.codec.trim().to_string(),
+        }
+    }
+}
+
+// Set on files the app produced from another recording (trims, salvages).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Derivation {
+    pub source: String,
+    pub operation: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingEntry {
+    pub file: String,
+    pub recorded_at: String,
+    pub duration_secs: f64,
+    #[serde(flatten)]
+    pub details: CaptureDetails,
+    pub exit_status: String,
+    pub file_size: u64,
+    pub log_file: Option<String>,
+    pub warnings: Vec<String>,
+    pub derived_from: Option<Derivation>,
+    #[serde(skip)]
+    pub missing: bool,
+}
+
+impl RecordingEntry {
+    pub fn new(
+        file: &str,
+        started: DateTime<Local>,
+        duration: Duration,
+        details: CaptureDetails,
+        exit_status: &str,
+    ) -> Self {
+        let mut entry = Self {
+            file: file.to_string(),
+            recorded_at: started.format("%Y-%m-%d %H:%M:%S").to_string(),
+            duration_secs: duration.as_secs_f64(),
+            details,
+            exit_status: exit_status.to_string(),
+            file_size: 0,
+            log_file: None,
+            warnings: Vec::new(),
+            derived_from: None,
+            missing: false,
+        };
+        entry.refresh();
+        entry
+    }
+
+    // A new file made from this one keeps its capture details and session log.
+    pub fn derive(&self, file: &str, operation: &str) -> Self {
+        let mut entry = Self {
+            file: file.to_string(),
+            exit_status: String::new(),
+            warnings: Vec::new(),
+            derived_from: Some(Derivation {
+                source: self.file.clone(),
+                operation: operation.to_string(),
+            }),
+            ..self.clone()
+        };
+        entry.refresh();
+        entry
+    }
+
+    pub fn file_name(&self) -> String {
+        Path::new(&self.file)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.file.clone())
+    }
+
+    // Notices files that were moved or deleted outside the app.
+    pub fn refresh(&mut self) {
+        match fs::metadata(&self.file) {
+            Ok(metadata) => {
+                self.missing = false;
+                self.file_size = metadata.len();
+            }
+            Err(_) => self.missing = true,
+        }
+    }
+}
+
+// Newest recordings first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingHistory {
+    pub version: u64,
+    pub entries: Vec<RecordingEntry>,
+}
+
+impl Default for RecordingHistory {
+    fn default() -> Self {
+        Self {
+            version: HISTORY_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl RecordingHistory {
+    pub fn add(&mut self, entry: RecordingEntry) {
+        self.entries.retain(|existing| existing.file != entry.file);
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_HISTORY_ENTRIES);
+    }
+
+    // Returns false if the file is no longer in the library.
+    pub fn set_warnings(&mut self, file: &str, warnings: Vec<String>) -> bool {
+        match self.entries.iter_mut().find(|entry| entry.file == file) {
+            Some(entry) => {
+                entry.warnings = warnings;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        for entry in &mut self.entries {
+            entry.refresh();
+        }
+    }
+
+    // Renames the file in place; `new_name` is a bare file name.
+    pub fn rename(&mut self, index: usize, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() || new_name.contains('/') || new_name == "." || new_name == ".." {
+            return Err(format!("\"{new_name}\" is not a valid file name."));
+        }
+        let entry = self
+            .entries
+            .get_mut(index)
+            .ok_or_else(|| "That recording is no longer in the library.".to_string())?;
+        let target = Path::new(&entry.file).with_file_name(new_name);
+        if target.exists() {
+            return Err(format!("{} already exists.", target.display()));
+        }
+        fs::rename(&entry.file, &target)
+            .map_err(|err| format!("Failed to rename {}: {err}", entry.file))?;
+        let sidecar = sidecar_path(&entry.file);
+        if sidecar.exists() {
+            let _ = fs::rename(&sidecar, sidecar_path(&target.to_string_lossy()));
+        }
+        entry.file = target.to_string_lossy().into_owned();
+        entry.refresh();
+        Ok(())
+    }
+
+    // Deletes the file (if it is still there) and drops the entry.
+    pub fn delete(&mut self, index: usize) -> Result<(), String> {
+        let Some(entry) = self.entries.get(index) else {
+            return Ok(());
+        };
+        match fs::remove_file(&entry.file) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("Failed to delete {}: {err}", entry.file)),
+        }
+        let _ = fs::remove_file(sidecar_path(&entry.file));
+        self.entries.remove(index);
+        Ok(())
+    }
+
+    pub fn forget(&mut self, index: usize) {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+        }
+    }
+}
+
+pub fn history_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(HISTORY_FILE_NAME))
+}
+
+pub fn load_history() -> Result<RecordingHistory, String> {
+    let Some(path) = history_path() else {
+        return Err("Cannot locate a state directory (HOME is not set).".to_string());
+    };
+    load_history_from(&path)
+}
+
+pub fn load_history_from(path: &Path) -> Result<RecordingHistory, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(RecordingHistory::default());
+        }
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+    let mut history: RecordingHistory = serde_json::from_str(&contents)
+        .map_err(|err| format!("Ignoring unreadable history {}: {err}", path.display()))?;
+    history.version = HISTORY_VERSION;
+    history.refresh();
+    Ok(history)
+}
+
+pub fn save_history(history: &RecordingHistory) -> Result<(), String> {
+    let Some(path) = history_path() else {
+        return Err("Cannot locate a state directory (HOME is not set).".to_string());
+    };
+    save_history_to(&path, history)
+}
+
+pub fn save_history_to(path: &Path, history: &RecordingHistory) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(history)
+        .map_err(|err| format!("Failed to serialize recording history: {err}"))?;
+
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .map_err(|err| format!("Failed to write {}: {err}", temp_path.display()))?;
+    fs::rename(&temp_path, path)
+        .map_err(|err| format!("Failed to replace {}: {err}", path.display()))
+}
+
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CaptureDetails, RecordingEntry, RecordingHistory, format_file_size, load_history_from,
+        save_history_to,
+    };
+    use chrono::Local;
+    use std::time::Duration;
+    use std::{env, fs, process};
+
+    #[test]
+    fn history_tracks_renames_deletes_and_missing_files() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-history-{}", process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let first = dir.join("first.mp4");
+        let second = dir.join("second.mp4");
+        fs::write(&first, b"12345").expect("file should be written");
+        fs::write(&second, b"").expect("file should be written");
+
+        let mut history = RecordingHistory::default();
+        for file in [&first, &second] {
+            history.add(RecordingEntry::new(
+                &file.to_string_lossy(),
+                Local::now(),
+                Duration::from_secs(3),
+                CaptureDetails::default(),
+                "exit code 0",
+            ));
+        }
+        assert_eq!(history.entries[0].file_name(), "second.mp4");
+        assert_eq!(history.entries[1].file_size, 5);
+
+        assert!(history.rename(1, "../escape.mp4").is_err());
+        assert!(history.rename(1, "second.mp4").is_err());
+        history
+            .rename(1, "renamed.mp4")
+            .expect("rename should work");
+        assert!(dir.join("renamed.mp4").exists());
+        assert_eq!(history.entries[1].file_name(), "renamed.mp4");
+
+        history.delete(0).expect("delete should work");
+        assert!(!second.exists());
+        assert_eq!(history.entries.len(), 1);
+
+        let path = dir.join("history.json");
+        save_history_to(&path, &history).expect("history should save");
+        fs::remove_file(dir.join("renamed.mp4")).expect("file should be removed");
+        let loaded = load_history_from(&path).expect("history should load");
+        assert_eq!(loaded.entries.len(), 1);
+        assert!(loaded.entries[0].missing);
+
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(format_file_size(1536), "1.5 KB");
+    }
+}
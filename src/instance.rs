@@ -0,0 +1,237 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths::runtime_dir;
+
+const SESSION_LOCK_FILE_NAME: &str = "session.lock";
+const PID_FILE_PREFIX: &str = "recorder-";
+const PID_FILE_SUFFIX: &str = ".json";
+
+pub struct SessionLock {
+    _file: File,
+}
+
+impl SessionLock {
+    pub fn acquire() -> Result<Self, String> {
+        Self::acquire_at(&session_lock_path())
+    }
+
+    pub fn acquire_at(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(parent)
+                .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+
+        let res = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+        if res != 0 {
+            let mut holder = String::new();
+            let _ = file.read_to_string(&mut holder);
+            let holder = holder.trim();
+            return Err(if holder.is_empty() {
+                "Another wf-recorder-ui session is already recording.".to_string()
+            } else {
+                format!("Another wf-recorder-ui session (pid {holder}) is already recording.")
+            });
+        }
+
+        let _ = file.set_len(0);
+        let _ = file.rewind();
+        let _ = write!(file, "{}", std::process::id());
+        let _ = file.flush();
+        Ok(Self { _file: file })
+    }
+}
+
+pub fn session_lock_path() -> PathBuf {
+    runtime_dir().join(SESSION_LOCK_FILE_NAME)
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecorderPidRecord {
+    pub pid: u32,
+    pub owner_pid: u32,
+    pub output_file: String,
+    pub started_unix: u64,
+}
+
+impl RecorderPidRecord {
+    pub fn new(pid: u32, output_file: &str) -> Self {
+        Self {
+            pid,
+            owner_pid: std::process::id(),
+            output_file: output_file.to_string(),
+            started_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn running_for(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH + Duration::from_secs(self.started_unix))
+            .unwrap_or_default()
+    }
+}
+
+pub struct RecorderPidFile {
+    path: PathBuf,
+}
+
+impl RecorderPidFile {
+    pub fn create(record: &RecorderPidRecord) -> Result<Self, String> {
+        Self::create_in(&runtime_dir(), record)
+    }
+
+    pub fn create_in(dir: &Path, record: &RecorderPidRecord) -> Result<Self, String> {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        let path = dir.join(format!(
+            "{PID_FILE_PREFIX}{}{PID_FILE_SUFFIX}",
+            record.owner_pid
+        ));
+        let json = serde_json::to_string(record)
+            .map_err(|err| format!("Failed to serialize PID file: {err}"))?;
+        fs::write(&path, json)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for RecorderPidFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub fn find_stray_recorders() -> Vec<RecorderPidRecord> {
+    find_stray_recorders_in(&runtime_dir())
+}
+
+// A recorder is stray when its owner is gone but wf-recorder itself is still
+// alive; records whose recorder has exited are cleaned up along the way.
+pub fn find_stray_recorders_in(dir: &Path) -> Vec<RecorderPidRecord> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut strays = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !name.starts_with(PID_FILE_PREFIX) || !name.ends_with(PID_FILE_SUFFIX) {
+            continue;
+        }
+        let record = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<RecorderPidRecord>(&json).ok());
+        let Some(record) = record else {
+            let _ = fs::remove_file(&path);
+            continue;
+        };
+        if !is_recorder_process(record.pid) {
+            let _ = fs::remove_file(&path);
+        } else if record.owner_pid != std::process::id() && !process_alive(record.owner_pid) {
+            strays.push(record);
+        }
+    }
+    strays.sort_by_key(|record| record.started_unix);
+    strays
+}
+
+pub fn forget_recorder(record: &RecorderPidRecord) {
+    forget_recorder_in(&runtime_dir(), record);
+}
+
+fn forget_recorder_in(dir: &Path, record: &RecorderPidRecord) {
+    let _ = fs::remove_file(dir.join(format!(
+        "{PID_FILE_PREFIX}{}{PID_FILE_SUFFIX}",
+        record.owner_pid
+    )));
+}
+
+pub fn process_alive(pid: u32) -> bool {
+    let Ok(stat) = fs::read_to_string(format!("/proc/{pid}/stat")) else {
+        return false;
+    };
+    // The state follows the parenthesised command name, which may itself contain spaces.
+    let state = stat
+        .rsplit_once(')')
+        .and_then(|(_, rest)| rest.split_whitespace().next());
+    !matches!(state, None | Some("Z") | Some("X"))
+}
+
+pub fn is_recorder_process(pid: u32) -> bool {
+    process_alive(pid)
+        && fs::read_to_string(format!("/proc/{pid}/comm"))
+            .is_ok_and(|comm| comm.trim() == "wf-recorder")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RecorderPidFile, RecorderPidRecord, SessionLock, find_stray_recorders_in, process_alive,
+    };
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn second_session_lock_is_refused_until_first_is_dropped() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-lock-{}", process::id()));
+        let path = dir.join("session.lock");
+
+        let first = SessionLock::acquire_at(&path).expect("first lock should succeed");
+        let err = SessionLock::acquire_at(&path)
+            .err()
+            .expect("second lock should fail");
+        assert!(err.contains(&process::id().to_string()));
+
+        drop(first);
+        SessionLock::acquire_at(&path).expect("lock should be free again");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn pid_files_for_exited_recorders_are_cleaned_up() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-pid-{}", process::id()));
+        assert!(process_alive(process::id()));
+
+        let mut record = RecorderPidRecord::new(u32::MAX - 1, "/tmp/out.mp4");
+        record.owner_pid = u32::MAX - 2;
+        let pid_file = RecorderPidFile::create_in(&dir, &record).expect("pid file is written");
+        std::mem::forget(pid_file);
+        assert!(find_stray_recorders_in(&dir).is_empty());
+        assert_eq!(
+            std::fs::read_dir(&dir).expect("dir exists").count(),
+            0,
+            "stale record should be removed"
+        );
+
+        let record = RecorderPidRecord::new(u32::MAX - 1, "/tmp/out.mp4");
+        let pid_file = RecorderPidFile::create_in(&dir, &record).expect("pid file is written");
+        assert_eq!(std::fs::read_dir(&dir).expect("dir exists").count(), 1);
+        drop(pid_file);
+        assert_eq!(std::fs::read_dir(&dir).expect("dir exists").count(), 0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
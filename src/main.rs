@@ -1,20 +1,102 @@
 mod actions;
 mod app;
+mod capabilities;
+mod cli;
+mod codec_params;
+mod compat;
 mod config;
 mod constants;
+mod control;
 mod discovery;
+mod export;
+mod headless;
+mod history;
+mod instance;
 mod models;
+mod paths;
+mod profiles;
+mod quality;
+mod recorder;
+mod recovery;
+mod schedule;
+mod session_log;
+mod settings;
+mod sidecar;
+mod trim;
+mod verify;
 
 use app::RecorderApp;
+use cli::{CliCommand, USAGE, parse_args};
+use control::{ControlRequest, instance_running, send_request, socket_path};
 use eframe::NativeOptions;
 
 fn main() {
+    match parse_args(std::env::args().skip(1)) {
+        Ok(CliCommand::Gui) => run_gui(),
+        Ok(CliCommand::Help) => print!("{USAGE}"),
+        Ok(CliCommand::Record(options)) => std::process::exit(headless::run_record(options)),
+        Ok(CliCommand::Control(request)) => std::process::exit(run_control(&request)),
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run_gui() {
+    let socket = socket_path();
+    if instance_running(&socket) {
+        match send_request(&socket, &ControlRequest::Focus) {
+            Ok(response) if response.ok => {
+                eprintln!("wf-recorder UI is already running; focused the existing window.");
+                return;
+            }
+            Ok(response) => eprintln!(
+                "The running wf-recorder UI did not accept focus: {}",
+                response.message.unwrap_or_default()
+            ),
+            Err(err) => eprintln!("{err}"),
+        }
+        // Only one UI may own the control socket.
+        eprintln!("wf-recorder UI is already running; not starting a second window.");
+        std::process::exit(1);
+    }
+
     let native_options = NativeOptions::default();
     if let Err(err) = eframe::run_native(
         "wf-recorder UI",
         native_options,
-        Box::new(|_cc| Box::new(RecorderApp::new())),
+        Box::new(|cc| {
+            let mut app = RecorderApp::new();
+            app.start_control_server(cc.egui_ctx.clone());
+            Box::new(app)
+        }),
     ) {
         eprintln!("Failed to start wf-recorder UI: {err}");
     }
 }
+
+fn run_control(request: &ControlRequest) -> i32 {
+    match send_request(&socket_path(), request) {
+        Ok(response) => {
+            if let Some(message) = &response.message {
+                if response.ok {
+                    println!("{message}");
+                } else {
+                    eprintln!("{message}");
+                }
+            }
+            if let Some(status) = &response.status
+                && matches!(request, ControlRequest::Status)
+                && let Ok(json) = serde_json::to_string_pretty(status)
+            {
+                println!("{json}");
+            }
+            if response.ok { 0 } else { 1 }
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
@@ -1,29 +1,126 @@
-use std::process::Child;
+use chrono::{DateTime, Local};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Child, ExitStatus};
+use std::sync::Arc;
 use std::thread::JoinHandle;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use crate::config::{RecorderConfig, segment_file};
+use crate::history::CaptureDetails;
+use crate::instance::{RecorderPidFile, SessionLock, process_alive};
+use crate::recorder::{RestartPolicy, StopProgress};
+use crate::schedule::RecordingLimits;
+use crate::session_log::SessionLog;
 
 #[derive(Default)]
 pub enum RecorderStatus {
     #[default]
     Idle,
+    Scheduled(PendingStart),
     Running(RecorderProcess),
+    Stopping(RecorderProcess, StopProgress),
+    Paused(RecordingSession),
 }
 
 impl RecorderStatus {
     pub fn is_running(&self) -> bool {
-        matches!(self, Self::Running(_))
+        self.process().is_some()
+    }
+
+    pub fn is_active(&self) -> bool {
+        !matches!(self, Self::Idle)
+    }
+
+    pub fn process(&self) -> Option<&RecorderProcess> {
+        match self {
+            Self::Idle | Self::Scheduled(_) | Self::Paused(_) => None,
+            Self::Running(process) | Self::Stopping(process, _) => Some(process),
+        }
+    }
+}
+
+// A start that waits for a countdown or a wall-clock time before spawning
+// wf-recorder; cancelling it is just dropping back to Idle.
+pub struct PendingStart {
+    pub start_at: Instant,
+    pub scheduled_for: Option<String>,
+}
+
+impl PendingStart {
+    // Rounded up so a 5 second countdown reads 5, 4, … 1 rather than 4 … 0.
+    pub fn remaining_rounded(&self) -> Duration {
+        let remaining = self.start_at.saturating_duration_since(Instant::now());
+        Duration::from_secs(remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        now >= self.start_at
+    }
+}
+
+pub enum RecorderHandle {
+    Spawned(Child),
+    Adopted(u32),
+}
+
+pub enum ProcessExit {
+    Exited(ExitStatus),
+    Vanished,
+}
+
+impl RecorderHandle {
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::Spawned(child) => child.id(),
+            Self::Adopted(pid) => *pid,
+        }
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<ProcessExit>> {
+        match self {
+            Self::Spawned(child) => Ok(child.try_wait()?.map(ProcessExit::Exited)),
+            Self::Adopted(pid) => Ok((!process_alive(*pid)).then_some(ProcessExit::Vanished)),
+        }
     }
 }
 
 pub struct RecorderProcess {
-    pub child: Child,
+    pub handle: RecorderHandle,
     pub stdout_join: Option<JoinHandle<()>>,
     pub stderr_join: Option<JoinHandle<()>>,
     pub started_at: Instant,
-    pub output_file: String,
+    pub segment_file: String,
+    pub pid_file: Option<RecorderPidFile>,
+    pub session: RecordingSession,
 }
 
 impl RecorderProcess {
+    pub fn output_file(&self) -> &str {
+        &self.session.output_file
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.session.recorded + self.started_at.elapsed()
+    }
+
+    pub fn recorded_bytes(&self) -> u64 {
+        self.session
+            .parts
+            .iter()
+            .chain(
+                self.session
+                    .rotation
+                    .iter()
+                    .flat_map(|rotation| &rotation.chunks),
+            )
+            .chain(std::iter::once(&self.segment_file))
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
     pub fn finish(&mut self) {
         if let Some(handle) = self.stdout_join.take() {
             let _ = handle.join();
@@ -31,6 +128,111 @@ impl RecorderProcess {
         if let Some(handle) = self.stderr_join.take() {
             let _ = handle.join();
         }
+        self.pid_file = None;
+    }
+}
+
+// One logical recording, possibly split into several wf-recorder runs by
+// pausing. `args` always point at `output_file`; resumed segments swap in a
+// part file and are joined back into `output_file` on the final stop.
+pub struct RecordingSession {
+    pub args: Vec<String>,
+    pub output_file: String,
+    pub parts: Vec<String>,
+    pub recorded: Duration,
+    pub pause_requested: bool,
+    pub limits: RecordingLimits,
+    pub auto_stop_reason: Option<String>,
+    pub rotation: Option<Rotation>,
+    pub restart_policy: Option<RestartPolicy>,
+    // Consecutive restarts; a healthy run resets it, `total_restarts` does not.
+    pub restarts: u32,
+    pub total_restarts: u32,
+    pub restart_at: Option<Instant>,
+    pub started: DateTime<Local>,
+    pub details: CaptureDetails,
+    pub config: Option<RecorderConfig>,
+    pub last_exit: String,
+    pub log: Option<Arc<SessionLog>>,
+    // Final file of an MKV-first recording; `output_file` is the intermediate.
+    pub remux_to: Option<String>,
+    pub unclean_exit: bool,
+    _session_lock: Option<SessionLock>,
+}
+
+impl RecordingSession {
+    pub fn new(args: Vec<String>, output_file: String, session_lock: Option<SessionLock>) -> Self {
+        Self {
+            args,
+            output_file,
+            parts: Vec::new(),
+            recorded: Duration::ZERO,
+            pause_requested: false,
+            limits: RecordingLimits::default(),
+            auto_stop_reason: None,
+            rotation: None,
+            restart_policy: None,
+            restarts: 0,
+            total_restarts: 0,
+            restart_at: None,
+            started: Local::now(),
+            details: CaptureDetails::default(),
+            config: None,
+            last_exit: String::new(),
+            log: None,
+            remux_to: None,
+            unclean_exit: false,
+            _session_lock: session_lock,
+        }
+    }
+
+    pub fn can_pause(&self) -> bool {
+        !self.args.is_empty()
+    }
+
+    // An explicit stop while a pause, restart or rotation is being finalized
+    // ends the recording instead.
+    pub fn cancel_transitions(&mut self) {
+        self.pause_requested = false;
+        self.restart_at = None;
+        if let Some(rotation) = &mut self.rotation {
+            rotation.rotate_requested = false;
+        }
+    }
+
+    pub fn restart_due(&self, now: Instant) -> bool {
+        self.restart_at.is_some_and(|restart_at| now >= restart_at)
+    }
+
+    pub fn next_segment_file(&self) -> String {
+        match &self.rotation {
+            Some(rotation) => segment_file(&rotation.pattern, rotation.chunks.len() + 1),
+            // Pause parts skip numbers that are already taken on disk.
+            None => (self.parts.len() + 1..)
+                .map(|index| segment_file(&self.output_file, index))
+                .find(|part| !Path::new(part).exists())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// Segmented recordings keep every wf-recorder run as its own numbered file
+// instead of joining them; pausing simply ends the current chunk.
+pub struct Rotation {
+    pub limits: RecordingLimits,
+    pub pattern: String,
+    pub chunks: Vec<String>,
+    pub rotate_requested: bool,
+}
+
+impl Rotation {
+    pub fn new(limits: RecordingLimits, pattern: String) -> Self {
+        Self {
+            limits,
+            pattern,
+            chunks: Vec::new(),
+            rotate_requested: false,
+        }
     }
 }
 
@@ -44,6 +246,17 @@ pub struct LogEntry {
 pub enum LogSource {
     Stdout,
     Stderr,
+    Supervisor,
+}
+
+impl LogSource {
+    pub fn tag(self) -> &'static str {
+        match self {
+            LogSource::Stdout => "[stdout] ",
+            LogSource::Stderr => "[stderr] ",
+            LogSource::Supervisor => "[supervisor] ",
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -66,9 +279,65 @@ pub struct AudioDevice {
     pub kind: AudioDeviceKind,
 }
 
+// A DRM render node usable as wf-recorder's `--device`.
 #[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderNode {
+    pub path: String,
+    pub driver: Option<String>,
+}
+
+impl RenderNode {
+    pub fn label(&self) -> String {
+        match &self.driver {
+            Some(driver) => format!("{} ({driver})", self.path),
+            None => self.path.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct WindowChoice {
     pub id: String,
     pub label: String,
     pub geometry: String,
+    pub app: String,
+    pub title: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordingSession;
+    use std::time::{Duration, Instant};
+    use std::{env, fs, process};
+
+    #[test]
+    fn stopping_during_a_pause_ends_the_recording() {
+        let mut session = RecordingSession::new(
+            vec!["--file".to_string(), "/v/demo.mp4".to_string()],
+            "/v/demo.mp4".to_string(),
+            None,
+        );
+        session.pause_requested = true;
+        session.restart_at = Some(Instant::now() + Duration::from_secs(5));
+        session.cancel_transitions();
+        assert!(!session.pause_requested);
+        assert!(!session.restart_due(Instant::now() + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn pause_parts_skip_files_that_already_exist() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-parts-{}", process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let output = dir.join("demo.mp4").to_string_lossy().into_owned();
+        fs::write(dir.join("demo-001.mp4"), b"old").expect("file should be written");
+
+        let mut session = RecordingSession::new(Vec::new(), output, None);
+        let first = session.next_segment_file();
+        assert!(first.ends_with("demo-002.mp4"));
+        fs::write(&first, b"part").expect("file should be written");
+        session.parts.push(first);
+        assert!(session.next_segment_file().ends_with("demo-003.mp4"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,41 @@
+use std::env;
+use std::path::PathBuf;
+
+pub const APP_DIR_NAME: &str = "wf-recorder-ui";
+
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+pub fn runtime_dir() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(value) if !value.is_empty() && PathBuf::from(&value).is_absolute() => {
+            PathBuf::from(value).join(APP_DIR_NAME)
+        }
+        _ => {
+            let uid = unsafe { libc::getuid() };
+            env::temp_dir().join(format!("{APP_DIR_NAME}-{uid}"))
+        }
+    }
+}
+
+fn xdg_dir(variable: &str, home_fallback: &str) -> Option<PathBuf> {
+    let base = match env::var_os(variable) {
+        Some(value) if !value.is_empty() && PathBuf::from(&value).is_absolute() => {
+            PathBuf::from(value)
+        }
+        _ => {
+            let home = env::var_os("HOME").filter(|home| !home.is_empty())?;
+            PathBuf::from(home).join(home_fallback)
+        }
+    };
+    Some(base.join(APP_DIR_NAME))
+}
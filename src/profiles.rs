@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::RecorderConfig;
+
+pub const DEFAULT_PROFILE_NAME: &str = "Default";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub config: RecorderConfig,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE_NAME.to_string(),
+            config: RecorderConfig::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileStore {
+    pub profiles: Vec<Profile>,
+    pub active: String,
+    pub default_profile: String,
+}
+
+impl Default for ProfileStore {
+    fn default() -> Self {
+        Self::with_config(RecorderConfig::default())
+    }
+}
+
+impl ProfileStore {
+    pub fn with_config(config: RecorderConfig) -> Self {
+        Self {
+            profiles: vec![Profile {
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                config,
+            }],
+            active: DEFAULT_PROFILE_NAME.to_string(),
+            default_profile: DEFAULT_PROFILE_NAME.to_string(),
+        }
+    }
+
+    pub fn normalize(&mut self) {
+        self.profiles
+            .retain(|profile| !profile.name.trim().is_empty());
+        let mut seen: Vec<String> = Vec::new();
+        self.profiles.retain(|profile| {
+            if seen.contains(&profile.name) {
+                false
+            } else {
+                seen.push(profile.name.clone());
+                true
+            }
+        });
+        if self.profiles.is_empty() {
+            self.profiles.push(Profile::default());
+        }
+        if self.get(&self.default_profile).is_none() {
+            self.default_profile = self.profiles[0].name.clone();
+        }
+        if self.get(&self.active).is_none() {
+            self.active = self.default_profile.clone();
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+    }
+
+    pub fn active_config(&self) -> RecorderConfig {
+        self.get(&self.active)
+            .map(|profile| profile.config.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn store_active_config(&mut self, config: &RecorderConfig) {
+        let active = self.active.clone();
+        if let Some(profile) = self.get_mut(&active) {
+            profile.config = config.clone();
+        }
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<RecorderConfig, String> {
+        let config = self
+            .get(name)
+            .map(|profile| profile.config.clone())
+            .ok_or_else(|| format!("No profile named \"{name}\"."))?;
+        self.active = name.to_string();
+        Ok(config)
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<(), String> {
+        if self.get(name).is_none() {
+            return Err(format!("No profile named \"{name}\"."));
+        }
+        self.default_profile = name.to_string();
+        Ok(())
+    }
+
+    pub fn create(&mut self, name: &str, config: RecorderConfig) -> Result<(), String> {
+        let name = self.validate_new_name(name)?;
+        self.profiles.push(Profile { name, config });
+        Ok(())
+    }
+
+    pub fn duplicate(&mut self, source: &str, name: &str) -> Result<(), String> {
+        let config = self
+            .get(source)
+            .map(|profile| profile.config.clone())
+            .ok_or_else(|| format!("No profile named \"{source}\"."))?;
+        self.create(name, config)
+    }
+
+    pub fn rename(&mut self, old_name: &str, new_name: &str) -> Result<(), String> {
+        if old_name == new_name.trim() {
+            return Ok(());
+        }
+        let new_name = self.validate_new_name(new_name)?;
+        let profile = self
+            .get_mut(old_name)
+            .ok_or_else(|| format!("No profile named \"{old_name}\"."))?;
+        profile.name = new_name.clone();
+        if self.active == old_name {
+            self.active = new_name.clone();
+        }
+        if self.default_profile == old_name {
+            self.default_profile = new_name;
+        }
+        Ok(())
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        if self.profiles.len() <= 1 {
+            return Err("Cannot delete the only remaining profile.".to_string());
+        }
+        let index = self
+            .profiles
+            .iter()
+            .position(|profile| profile.name == name)
+            .ok_or_else(|| format!("No profile named \"{name}\"."))?;
+        self.profiles.remove(index);
+        if self.default_profile == name {
+            self.default_profile = self.profiles[0].name.clone();
+        }
+        if self.active == name {
+            self.active = self.default_profile.clone();
+        }
+        Ok(())
+    }
+
+    pub fn unique_name(&self, base: &str) -> String {
+        let base = base.trim();
+        if self.get(base).is_none() {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{base} {n}"))
+            .find(|candidate| self.get(candidate).is_none())
+            .unwrap_or_else(|| base.to_string())
+    }
+
+    fn validate_new_name(&self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty.".to_string());
+        }
+        if self.get(name).is_some() {
+            return Err(format!("A profile named \"{name}\" already exists."));
+        }
+        Ok(name.to_string())
+    }
+}
+
+pub fn fields_differing_from_default(config: &RecorderConfig) -> Vec<String> {
+    let (Ok(Value::Object(current)), Ok(Value::Object(defaults))) = (
+        serde_json::to_value(config),
+        serde_json::to_value(RecorderConfig::default()),
+    ) else {
+        return Vec::new();
+    };
+
+    current
+        .iter()
+        .filter(|(key, value)| defaults.get(*key) != Some(*value))
+        .map(|(key, _)| key.replace('_', " "))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_PROFILE_NAME, ProfileStore, fields_differing_from_default};
+    use crate::config::{CaptureMode, RecorderConfig};
+
+    #[test]
+    fn rename_and_delete_keep_active_and_default_consistent() {
+        let mut store = ProfileStore::default();
+        store
+            .duplicate(DEFAULT_PROFILE_NAME, "demo")
+            .expect("duplicate should succeed");
+        store.set_default("demo").expect("demo exists");
+        store.set_active("demo").expect("demo exists");
+
+        store
+            .rename("demo", "4K demo")
+            .expect("rename should succeed");
+        assert_eq!(store.active, "4K demo");
+        assert_eq!(store.default_profile, "4K demo");
+        assert!(store.rename("4K demo", DEFAULT_PROFILE_NAME).is_err());
+
+        store.delete("4K demo").expect("delete should succeed");
+        assert_eq!(store.active, DEFAULT_PROFILE_NAME);
+        assert_eq!(store.default_profile, DEFAULT_PROFILE_NAME);
+        assert!(store.delete(DEFAULT_PROFILE_NAME).is_err());
+    }
+
+    #[test]
+    fn reports_fields_that_differ_from_defaults() {
+        let config = RecorderConfig {
+            capture_mode: CaptureMode::Area,
+            codec: "gif".to_string(),
+            ..RecorderConfig::default()
+        };
+
+        let mut fields = fields_differing_from_default(&config);
+        fields.sort();
+        assert_eq!(fields, vec!["capture mode", "codec"]);
+        assert!(fields_differing_from_default(&RecorderConfig::default()).is_empty());
+    }
+}
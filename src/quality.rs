@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+use crate::codec_params::{get_param, set_param};
+use crate::config::{ParamEntry, RecorderConfig};
+use crate::verify::expected_codec_name;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityPreset {
+    Draft,
+    Standard,
+    High,
+    Lossless,
+    SmallFile,
+}
+
+impl QualityPreset {
+    pub const ALL: [QualityPreset; 5] = [
+        QualityPreset::Draft,
+        QualityPreset::Standard,
+        QualityPreset::High,
+        QualityPreset::Lossless,
+        QualityPreset::SmallFile,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QualityPreset::Draft => "Draft",
+            QualityPreset::Standard => "Standard",
+            QualityPreset::High => "High",
+            QualityPreset::Lossless => "Lossless",
+            QualityPreset::SmallFile => "Small file",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            QualityPreset::Draft => {
+                "Fastest encoding and visibly soft; fine for quick bug reports."
+            }
+            QualityPreset::Standard => "Sharp text at a reasonable size; the everyday choice.",
+            QualityPreset::High => "Near-transparent quality at 60 fps; larger files.",
+            QualityPreset::Lossless => {
+                "Bit-exact video where the encoder supports it; very large files."
+            }
+            QualityPreset::SmallFile => {
+                "Lower framerate and stronger compression for sharing over chat or email."
+            }
+        }
+    }
+}
+
+// What a preset turns into for one video and audio encoder.
+// `None` fields are left as the user set them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EffectiveSettings {
+    pub codec_params: Option<Vec<ParamEntry>>,
+    pub pixel_format: Option<String>,
+    // Empty keeps wf-recorder's variable framerate.
+    pub framerate: String,
+    // The `b` audio codec parameter; `None` for codecs without a bitrate.
+    pub audio_bitrate: Option<String>,
+    // Caveats, e.g. when the encoder has no true lossless mode.
+    pub notes: Vec<String>,
+}
+
+pub fn effective_settings(
+    preset: QualityPreset,
+    codec: &str,
+    audio_codec: &str,
+) -> EffectiveSettings {
+    use QualityPreset::*;
+
+    let codec = codec.trim();
+    let mut settings = EffectiveSettings::default();
+    let params = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(key, value)| ParamEntry {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let chroma = match preset {
+        Lossless => "yuv444p",
+        _ => "yuv420p",
+    };
+    let video = expected_codec_name(codec).unwrap_or_default();
+    settings.framerate = match (preset, video.as_str()) {
+        (Draft | SmallFile, "gif") => "10",
+        (Standard, "gif") => "15",
+        (High | Lossless, "gif") => "24",
+        (Draft, _) => "30",
+        (SmallFile, _) => "24",
+        (High, _) => "60",
+        (Standard | Lossless, _) => "",
+    }
+    .to_string();
+
+    match codec {
+        "libx264" | "libx265" => {
+            let (speed, crf) = match (preset, codec) {
+                (Draft, _) => ("ultrafast", "30"),
+                (Standard, "libx264") => ("veryfast", "23"),
+                (Standard, _) => ("veryfast", "26"),
+                (High, "libx264") => ("medium", "18"),
+                (High, _) => ("medium", "20"),
+                (Lossless, _) => ("ultrafast", "0"),
+                (SmallFile, "libx264") => ("slow", "28"),
+                (SmallFile, _) => ("slow", "30"),
+            };
+            // x265 has no lossless CRF; it needs its own switch.
+            let quality = match (preset, codec) {
+                (Lossless, "libx265") => ("x265-params", "lossless=1"),
+                _ => ("crf", crf),
+            };
+            settings.codec_params = Some(params(&[("preset", speed), quality]));
+            settings.pixel_format = Some(chroma.to_string());
+        }
+        "libvpx-vp9" => {
+            settings.codec_params = Some(match preset {
+                Draft => params(&[
+                    ("crf", "45"),
+                    ("b", "0"),
+                    ("deadline", "realtime"),
+                    ("cpu-used", "8"),
+                ]),
+                Standard => params(&[
+                    ("crf", "33"),
+                    ("b", "0"),
+                    ("deadline", "realtime"),
+                    ("cpu-used", "6"),
+                ]),
+                High => params(&[
+                    ("crf", "24"),
+                    ("b", "0"),
+                    ("deadline", "realtime"),
+                    ("cpu-used", "5"),
+                ]),
+                Lossless => params(&[
+                    ("lossless", "1"),
+                    ("deadline", "realtime"),
+                    ("cpu-used", "8"),
+                ]),
+                SmallFile => params(&[
+                    ("crf", "40"),
+                    ("b", "0"),
+                    ("deadline", "realtime"),
+                    ("cpu-used", "6"),
+                ]),
+            });
+            settings.pixel_format = Some(chroma.to_string());
+        }
+        codec if codec.ends_with("_vaapi") => {
+            let qp = match preset {
+                Draft => "32",
+                Standard => "24",
+                High => "18",
+                Lossless => "1",
+                SmallFile => "30",
+            };
+            settings.codec_params = Some(params(&[("rc_mode", "CQP"), ("qp", qp)]));
+            if preset == Lossless {
+                settings
+                    .notes
+                    .push("VAAPI has no lossless mode; QP 1 is as close as it gets.".to_string());
+            }
+        }
+        "gif" => {
+            settings.codec_params = Some(Vec::new());
+            settings.pixel_format = Some("rgb8".to_string());
+            if preset == Lossless {
+                settings.notes.push(
+                    "GIF is limited to 256 colours, so it is never truly lossless.".to_string(),
+                );
+            }
+        }
+        codec => settings.notes.push(format!(
+            "No quality mapping for {codec}; only the framerate and audio bitrate are set."
+        )),
+    }
+
+    let audio_codec = audio_codec.trim();
+    settings.audio_bitrate = match audio_codec {
+        "flac" | "alac" => None,
+        codec if codec.starts_with("pcm_") => None,
+        _ => Some(
+            match preset {
+                Draft => "64k",
+                Standard => "128k",
+                High => "192k",
+                Lossless => "320k",
+                SmallFile => "48k",
+            }
+            .to_string(),
+        ),
+    };
+    settings
+}
+
+// The preset-owned fields as they currently are, to notice manual edits.
+pub fn current_settings(config: &RecorderConfig) -> EffectiveSettings {
+    EffectiveSettings {
+        codec_params: Some(config.codec_params.clone()),
+        pixel_format: Some(config.pixel_format.clone()),
+        framerate: config.framerate.clone(),
+        audio_bitrate: get_param(&config.audio_codec_params, "b").map(str::to_string),
+        notes: Vec::new(),
+    }
+}
+
+// Rewrites the managed fields while a preset is attached; no-op in manual mode.
+pub fn apply_quality_preset(config: &mut RecorderConfig) {
+    let Some(preset) = config.quality_preset else {
+        return;
+    };
+    let audio_codec = match config.audio_codec.trim() {
+        "" => "aac",
+        codec => codec,
+    };
+    let settings = effective_settings(preset, &config.codec, audio_codec);
+    if let Some(params) = settings.codec_params {
+        config.codec_params = params;
+    }
+    if let Some(pixel_format) = settings.pixel_format {
+        config.pixel_format = pixel_format;
+    }
+    config.framerate = settings.framerate;
+    if get_param(&config.audio_codec_params, "b") != settings.audio_bitrate.as_deref() {
+        set_param(&mut config.audio_codec_params, "b", settings.audio_bitrate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QualityPreset, apply_quality_preset, effective_settings};
+    use crate::codec_params::get_param;
+    use crate::config::RecorderConfig;
+
+    #[test]
+    fn presets_translate_into_each_codecs_own_settings() {
+        let x264 = effective_settings(QualityPreset::High, "libx264", "aac");
+        assert_eq!(
+            get_param(x264.codec_params.as_deref().unwrap(), "crf"),
+            Some("18")
+        );
+        assert_eq!(x264.framerate, "60");
+        assert_eq!(x264.audio_bitrate.as_deref(), Some("192k"));
+
+        let vaapi = effective_settings(QualityPreset::Lossless, "hevc_vaapi", "flac");
+        assert_eq!(
+            get_param(vaapi.codec_params.as_deref().unwrap(), "qp"),
+            Some("1")
+        );
+        assert_eq!(vaapi.pixel_format, None);
+        assert_eq!(vaapi.audio_bitrate, None);
+        assert_eq!(vaapi.notes.len(), 1);
+
+        let mut config = RecorderConfig {
+            quality_preset: Some(QualityPreset::SmallFile),
+            codec: "libvpx-vp9".to_string(),
+            audio_codec: "libopus".to_string(),
+            ..RecorderConfig::default()
+        };
+        apply_quality_preset(&mut config);
+        assert_eq!(get_param(&config.codec_params, "crf"), Some("40"));
+        assert_eq!(get_param(&config.audio_codec_params, "b"), Some("48k"));
+        assert_eq!(config.framerate, "24");
+
+        config.codec = "libx264".to_string();
+        apply_quality_preset(&mut config);
+        assert_eq!(get_param(&config.codec_params, "deadline"), None);
+        assert_eq!(get_param(&config.codec_params, "preset"), Some("slow"));
+
+        config.quality_preset = None;
+        config.framerate = "50".to_string();
+        apply_quality_preset(&mut config);
+        assert_eq!(config.framerate, "50");
+    }
+}
@@ -0,0 +1,352 @@
+use std::fs;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+
+use crate::config::RecorderConfig;
+use crate::models::ProcessExit;
+
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+// Matches the range the settings UI allows, for hand-edited profiles too.
+const MIN_STOP_TIMEOUT: Duration = Duration::from_secs(1);
+const MAX_STOP_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub fn shell_preview(args: Vec<String>) -> String {
+    let mut preview = Vec::with_capacity(args.len() + 1);
+    preview.push("wf-recorder".to_string());
+    preview.extend(args.into_iter().map(shell_escape));
+    preview.join(" ")
+}
+
+fn shell_escape(arg: String) -> String {
+    if arg.is_empty() {
+        "''".to_string()
+    } else if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:@".contains(c))
+    {
+        arg
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+pub fn with_output_file(args: &[String], file: &str) -> Vec<String> {
+    let mut args = args.to_vec();
+    if let Some(index) = args.iter().position(|arg| arg == "--file")
+        && let Some(value) = args.get_mut(index + 1)
+    {
+        *value = file.to_string();
+    }
+    args
+}
+
+fn concat_list(parts: &[String]) -> String {
+    parts
+        .iter()
+        .map(|part| format!("file '{}'\n", part.replace('\'', "'\\''")))
+        .collect()
+}
+
+pub fn concat_segments(parts: &[String], output_file: &str) -> Result<(), String> {
+    let list_path = format!("{output_file}.parts.txt");
+    fs::write(&list_path, concat_list(parts))
+        .map_err(|err| format!("Failed to write {list_path}: {err}"))?;
+    let output = Command::new("ffmpeg")
+        .args([
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-y",
+            "-f",
+            "concat",
+            "-safe",
+            "0",
+        ])
+        .args(["-i", &list_path, "-c", "copy", output_file])
+        .stdin(Stdio::null())
+        .output();
+    let _ = fs::remove_file(&list_path);
+    let output = output.map_err(|err| format!("Failed to run ffmpeg: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffmpeg could not join the segments{}: {}",
+            format_exit_status(output.status),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    for part in parts {
+        let _ = fs::remove_file(part);
+    }
+    Ok(())
+}
+
+pub fn format_exit_status(status: ExitStatus) -> String {
+    if let Some(code) = status.code() {
+        format!(", exit code {code}")
+    } else {
+        #[cfg(unix)]
+        {
+            if let Some(signal) = status.signal() {
+                return format!(
+                    ", terminated by signal {} ({})",
+                    signal,
+                    signal_name(signal)
+                );
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            return ", terminated by signal".to_string();
+        }
+        #[cfg(unix)]
+        ", terminated by signal".to_string()
+    }
+}
+
+pub fn format_process_exit(exit: &ProcessExit) -> String {
+    match exit {
+        ProcessExit::Exited(status) => format_exit_status(*status),
+        ProcessExit::Vanished => ", exit status unknown (adopted process)".to_string(),
+    }
+}
+
+// If we die without stopping wf-recorder, the kernel sends it SIGINT so the
+// file is still finalized instead of being left recording with nobody reading it.
+#[cfg(target_os = "linux")]
+pub fn stop_with_parent(command: &mut Command) {
+    let parent = std::process::id() as libc::pid_t;
+    unsafe {
+        command.pre_exec(move || {
+            if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGINT) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if libc::getppid() != parent {
+                libc::raise(libc::SIGINT);
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn stop_with_parent(_command: &mut Command) {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub max_restarts: u32,
+    pub backoff: Duration,
+}
+
+impl RestartPolicy {
+    pub fn from_config(config: &RecorderConfig) -> Option<Self> {
+        (config.restart_on_failure && config.max_restarts > 0).then(|| Self {
+            max_restarts: config.max_restarts,
+            backoff: Duration::from_secs(config.restart_backoff_secs),
+        })
+    }
+
+    // Attempts are numbered from 1; the wait doubles each time, capped at a minute.
+    pub fn delay_for(&self, attempt: u32) -> Option<Duration> {
+        if attempt == 0 || attempt > self.max_restarts {
+            return None;
+        }
+        let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+        Some(self.backoff.saturating_mul(factor).min(MAX_RESTART_BACKOFF))
+    }
+
+    // A run that outlasted the longest backoff was healthy, so the next
+    // failure starts counting from 1 again instead of using up the budget.
+    pub fn next_attempt(&self, restarts: u32, ran: Duration) -> u32 {
+        let window = self.delay_for(self.max_restarts).unwrap_or(self.backoff);
+        if ran > window { 1 } else { restarts + 1 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopStage {
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
+impl StopStage {
+    pub fn next(self) -> Option<Self> {
+        match self {
+            Self::Interrupt => Some(Self::Terminate),
+            Self::Terminate => Some(Self::Kill),
+            Self::Kill => None,
+        }
+    }
+
+    pub fn signal_name(self) -> &'static str {
+        match self {
+            Self::Interrupt => "SIGINT",
+            Self::Terminate => "SIGTERM",
+            Self::Kill => "SIGKILL",
+        }
+    }
+
+    #[cfg(unix)]
+    fn signal(self) -> i32 {
+        match self {
+            Self::Interrupt => libc::SIGINT,
+            Self::Terminate => libc::SIGTERM,
+            Self::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+pub struct StopProgress {
+    pub stage: StopStage,
+    pub stage_started: Instant,
+    pub timeout: Duration,
+}
+
+impl StopProgress {
+    pub fn new(stage: StopStage, timeout: Duration) -> Self {
+        Self {
+            stage,
+            stage_started: Instant::now(),
+            timeout: timeout.clamp(MIN_STOP_TIMEOUT, MAX_STOP_TIMEOUT),
+        }
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        self.stage
+            .next()
+            .map(|_| self.timeout.saturating_sub(self.stage_started.elapsed()))
+    }
+
+    pub fn due_escalation(&self, now: Instant) -> Option<StopStage> {
+        let next = self.stage.next()?;
+        (now.saturating_duration_since(self.stage_started) >= self.timeout).then_some(next)
+    }
+
+    pub fn escalate(&mut self, pid: u32) -> std::io::Result<bool> {
+        let Some(next) = self.due_escalation(Instant::now()) else {
+            return Ok(false);
+        };
+        send_stop_signal(pid, next)?;
+        self.stage = next;
+        self.stage_started = Instant::now();
+        Ok(true)
+    }
+
+    pub fn outcome(&self) -> String {
+        match self.stage {
+            StopStage::Interrupt => "Stopped gracefully with SIGINT.".to_string(),
+            StopStage::Terminate => format!(
+                "wf-recorder did not finish within {}s of SIGINT and was stopped with SIGTERM.",
+                self.timeout.as_secs()
+            ),
+            StopStage::Kill => {
+                "wf-recorder was killed with SIGKILL; the file may be incomplete.".to_string()
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+pub fn send_stop_signal(pid: u32, stage: StopStage) -> std::io::Result<()> {
+    let res = unsafe { libc::kill(pid as i32, stage.signal()) };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send_stop_signal(_pid: u32, _stage: StopStage) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "Graceful stop is only supported on Unix platforms",
+    ))
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        libc::SIGINT => "SIGINT",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGALRM => "SIGALRM",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RestartPolicy, StopProgress, StopStage, concat_list, with_output_file};
+    use crate::config::RecorderConfig;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn restart_backoff_doubles_until_retries_run_out() {
+        assert_eq!(RestartPolicy::from_config(&RecorderConfig::default()), None);
+        let policy = RestartPolicy::from_config(&RecorderConfig {
+            restart_on_failure: true,
+            max_restarts: 8,
+            restart_backoff_secs: 2,
+            ..RecorderConfig::default()
+        })
+        .expect("policy should be enabled");
+        assert_eq!(policy.delay_for(1), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay_for(3), Some(Duration::from_secs(8)));
+        assert_eq!(policy.delay_for(8), Some(Duration::from_secs(60)));
+        assert_eq!(policy.delay_for(9), None);
+        assert_eq!(policy.next_attempt(8, Duration::from_secs(30)), 9);
+        assert_eq!(policy.next_attempt(8, Duration::from_secs(61)), 1);
+    }
+
+    #[test]
+    fn stop_progress_escalates_after_timeout_until_sigkill() {
+        assert_eq!(
+            StopProgress::new(StopStage::Interrupt, Duration::ZERO).timeout,
+            Duration::from_secs(1)
+        );
+        let mut progress = StopProgress::new(StopStage::Interrupt, Duration::from_secs(5));
+        let start = progress.stage_started;
+        assert_eq!(
+            progress.due_escalation(start + Duration::from_secs(4)),
+            None
+        );
+        assert_eq!(
+            progress.due_escalation(start + Duration::from_secs(5)),
+            Some(StopStage::Terminate)
+        );
+
+        progress.stage = StopStage::Kill;
+        assert_eq!(
+            progress.due_escalation(Instant::now() + Duration::from_secs(60)),
+            None
+        );
+        assert_eq!(progress.remaining(), None);
+        assert!(progress.outcome().contains("SIGKILL"));
+    }
+
+    #[test]
+    fn segments_swap_the_output_file_and_join_in_order() {
+        let args = vec![
+            "--codec".to_string(),
+            "libx264".to_string(),
+            "--file".to_string(),
+            "/tmp/demo.mp4".to_string(),
+        ];
+        assert_eq!(
+            with_output_file(&args, "/tmp/demo-002.mp4")[3],
+            "/tmp/demo-002.mp4"
+        );
+
+        assert_eq!(
+            concat_list(&["/tmp/a-001.mp4".to_string(), "/tmp/it's.mp4".to_string()]),
+            "file '/tmp/a-001.mp4'\nfile '/tmp/it'\\''s.mp4'\n"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::recorder::format_exit_status;
+use crate::trim::derived_path;
+use crate::verify::probe;
+
+// Containers that stay playable when the muxer never gets to finalize them.
+const CRASH_RESILIENT_FORMATS: [&str; 4] = ["mkv", "matroska", "webm", "ts"];
+
+pub fn is_crash_resilient(file_format: &str) -> bool {
+    CRASH_RESILIENT_FORMATS.contains(&file_format.trim().to_ascii_lowercase().as_str())
+}
+
+// Where an MKV-first recording is captured before being remuxed into
+// `output_file`; `None` when the output already is Matroska.
+pub fn intermediate_path(output_file: &str) -> Option<String> {
+    let extension = Path::new(output_file).extension()?.to_string_lossy();
+    if extension.eq_ignore_ascii_case("mkv") {
+        return None;
+    }
+    Some(derived_path(output_file, "recording", Some("mkv")))
+}
+
+// `demo.mp4` → `demo.recovered.mkv`, numbered like trims if that is taken.
+pub fn recovered_path(file: &str) -> String {
+    derived_path(file, "recovered", Some("mkv"))
+}
+
+// Stream-copies every track into a new container.
+pub fn remux(input: &str, output: &str) -> Result<(), String> {
+    run_ffmpeg(&["-i", input, "-map", "0", "-c", "copy", output])
+        .map_err(|err| format!("ffmpeg could not remux {input} into {output}{err}"))
+}
+
+pub struct Recovery {
+    pub recovered: Option<String>,
+    pub message: String,
+}
+
+// Probes `file` and, if ffprobe chokes on it, salvages whatever packets
+// ffmpeg can still read into a Matroska copy.
+pub fn recover(file: &str) -> Result<Recovery, String> {
+    if !Path::new(file).exists() {
+        return Err(format!("{file} does not exist."));
+    }
+    let problem = match probe(file) {
+        Ok(report) if !report.video_codecs.is_empty() && report.duration.is_some() => {
+            return Ok(Recovery {
+                recovered: None,
+                message: format!("ffprobe reads {file} without problems; nothing to recover."),
+            });
+        }
+        Ok(_) => "it has no readable video stream or duration".to_string(),
+        Err(err) => err,
+    };
+
+    let output = recovered_path(file);
+    let result = run_ffmpeg(&[
+        "-err_detect",
+        "ignore_err",
+        "-fflags",
+        "+genpts+discardcorrupt",
+        "-i",
+        file,
+        "-map",
+        "0",
+        "-c",
+        "copy",
+        &output,
+    ]);
+    let salvaged = probe(&output).is_ok_and(|report| !report.video_codecs.is_empty());
+    if !salvaged {
+        let _ = fs::remove_file(&output);
+        let detail = result.err().unwrap_or_default();
+        return Err(format!(
+            "Could not recover {file} ({problem}){detail}. MP4/MOV files killed before their index (moov atom) was written usually cannot be remuxed; record to MKV to avoid this."
+        ));
+    }
+    Ok(Recovery {
+        message: format!("Recovered {file} ({problem}) into {output}."),
+        recovered: Some(output),
+    })
+}
+
+// On failure the error is a suffix like ", exit code 1: <stderr>".
+pub fn run_ffmpeg<S: AsRef<OsStr>>(args: &[S]) -> Result<(), String> {
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-y"])
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!(": cannot run ffmpeg ({err})"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}: {}",
+            format_exit_status(output.status),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{intermediate_path, is_crash_resilient, recovered_path};
+
+    #[test]
+    fn recovery_paths_sit_next_to_the_original() {
+        assert_eq!(
+            intermediate_path("/videos/demo.mp4").as_deref(),
+            Some("/videos/demo.recording.mkv")
+        );
+        assert_eq!(intermediate_path("/videos/demo.mkv"), None);
+        assert_eq!(
+            recovered_path("/videos/demo.mp4"),
+            "/videos/demo.recovered.mkv"
+        );
+        assert!(is_crash_resilient("MKV"));
+        assert!(!is_crash_resilient("mp4"));
+    }
+}
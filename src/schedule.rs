@@ -0,0 +1,127 @@
+use chrono::{NaiveDateTime, NaiveTime};
+use std::time::Duration;
+
+use crate::config::RecorderConfig;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecordingLimits {
+    pub max_duration: Option<Duration>,
+    pub max_bytes: Option<u64>,
+}
+
+impl RecordingLimits {
+    pub fn new(max_duration_secs: u64, max_size_mb: u64) -> Self {
+        Self {
+            max_duration: (max_duration_secs > 0).then(|| Duration::from_secs(max_duration_secs)),
+            max_bytes: (max_size_mb > 0).then(|| max_size_mb.saturating_mul(BYTES_PER_MB)),
+        }
+    }
+
+    pub fn from_config(config: &RecorderConfig) -> Self {
+        Self::new(config.max_duration_secs, config.max_file_size_mb)
+    }
+
+    // Per-chunk limits for segmented recordings.
+    pub fn for_segments(config: &RecorderConfig) -> Self {
+        Self::new(
+            config.segment_minutes.saturating_mul(60),
+            config.segment_size_mb,
+        )
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_duration.is_none() && self.max_bytes.is_none()
+    }
+
+    // Returns the reason to stop, phrased for the recording summary.
+    pub fn exceeded(&self, elapsed: Duration, bytes: u64) -> Option<String> {
+        if let Some(max_duration) = self.max_duration
+            && elapsed >= max_duration
+        {
+            return Some(format!(
+                "Stopped automatically after reaching the {} duration limit.",
+                format_duration(max_duration)
+            ));
+        }
+        if let Some(max_bytes) = self.max_bytes
+            && bytes >= max_bytes
+        {
+            return Some(format!(
+                "Stopped automatically after the file reached {} MB.",
+                max_bytes / BYTES_PER_MB
+            ));
+        }
+        None
+    }
+}
+
+// Accepts HH:MM or HH:MM:SS and picks the next occurrence, so a time that
+// already passed today means tomorrow.
+pub fn delay_until(input: &str, now: NaiveDateTime) -> Result<Duration, String> {
+    let input = input.trim();
+    let time = NaiveTime::parse_from_str(input, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
+        .map_err(|_| format!("Cannot read start time \"{input}\"; use HH:MM or HH:MM:SS."))?;
+    let mut target = now.date().and_time(time);
+    if target <= now {
+        target += chrono::Duration::days(1);
+    }
+    (target - now)
+        .to_std()
+        .map_err(|err| format!("Invalid start time \"{input}\": {err}"))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, s) => format!("{h}h {m:02}m {s:02}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordingLimits, delay_until, format_duration};
+    use crate::config::RecorderConfig;
+    use chrono::NaiveDate;
+    use std::time::Duration;
+
+    #[test]
+    fn limits_trigger_on_duration_or_size() {
+        let config = RecorderConfig {
+            max_duration_secs: 90,
+            max_file_size_mb: 2,
+            ..RecorderConfig::default()
+        };
+        let limits = RecordingLimits::from_config(&config);
+        assert_eq!(limits.exceeded(Duration::from_secs(89), 0), None);
+        assert_eq!(
+            limits.exceeded(Duration::from_secs(90), 0).as_deref(),
+            Some("Stopped automatically after reaching the 1m 30s duration limit.")
+        );
+        assert!(
+            limits
+                .exceeded(Duration::ZERO, 2 * 1024 * 1024)
+                .expect("size limit should trigger")
+                .contains("2 MB")
+        );
+        assert!(RecordingLimits::from_config(&RecorderConfig::default()).is_unlimited());
+    }
+
+    #[test]
+    fn start_time_rolls_over_to_tomorrow() {
+        let now = NaiveDate::from_ymd_opt(2026, 3, 2)
+            .and_then(|date| date.and_hms_opt(14, 0, 0))
+            .expect("valid date");
+        assert_eq!(delay_until("14:30", now), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(
+            delay_until("13:59:59", now),
+            Ok(Duration::from_secs(24 * 3600 - 1))
+        );
+        assert!(delay_until("25:00", now).is_err());
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 02m 03s");
+    }
+}
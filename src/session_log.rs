@@ -0,0 +1,156 @@
+use chrono::{DateTime, Local};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::config::sanitize_file_component;
+use crate::models::LogSource;
+use crate::paths::state_dir;
+
+const LOGS_DIR_NAME: &str = "logs";
+const MAX_LOG_FILES: usize = 100;
+const MAX_LOG_AGE: Duration = Duration::from_secs(30 * 24 * 3600);
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+// wf-recorder output of one recording session, timestamped and tagged like
+// the in-app log, kept after the app closes.
+pub struct SessionLog {
+    path: PathBuf,
+    file: Mutex<(File, u64)>,
+}
+
+impl SessionLog {
+    pub fn create(output_file: &str, started: DateTime<Local>) -> Result<Self, String> {
+        let dir = logs_dir()
+            .ok_or_else(|| "Cannot locate a state directory (HOME is not set).".to_string())?;
+        Self::create_in(&dir, output_file, started)
+    }
+
+    pub fn create_in(
+        dir: &Path,
+        output_file: &str,
+        started: DateTime<Local>,
+    ) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        prune_logs(dir, MAX_LOG_FILES.saturating_sub(1), MAX_LOG_AGE);
+
+        let stem = Path::new(output_file)
+            .file_stem()
+            .map(|stem| sanitize_file_component(&stem.to_string_lossy()))
+            .unwrap_or_else(|| "recording".to_string());
+        let path = dir.join(format!("{}-{stem}.log", started.format("%Y%m%d-%H%M%S")));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+        let log = Self {
+            path,
+            file: Mutex::new((file, 0)),
+        };
+        log.write(
+            LogSource::Supervisor,
+            &format!("Recording to {output_file}"),
+        );
+        Ok(log)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&self, source: LogSource, line: &str) {
+        let Ok(mut guard) = self.file.lock() else {
+            return;
+        };
+        let (file, written) = &mut *guard;
+        if *written >= MAX_LOG_BYTES {
+            return;
+        }
+        let mut text = format!(
+            "{} {}{line}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            source.tag()
+        );
+        if *written + text.len() as u64 >= MAX_LOG_BYTES {
+            text = format!(
+                "{} {}Log reached {} MB; further output is only kept in memory.\n",
+                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                LogSource::Supervisor.tag(),
+                MAX_LOG_BYTES / (1024 * 1024)
+            );
+            *written = MAX_LOG_BYTES;
+        } else {
+            *written += text.len() as u64;
+        }
+        let _ = file.write_all(text.as_bytes());
+    }
+}
+
+pub fn logs_dir() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(LOGS_DIR_NAME))
+}
+
+// Keeps at most `keep` logs and drops any older than `max_age`.
+pub fn prune_logs(dir: &Path, keep: usize, max_age: Duration) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut logs: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "log"))
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, path)
+        })
+        .collect();
+    logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    let now = SystemTime::now();
+    for (index, (modified, path)) in logs.iter().enumerate() {
+        let expired = now.duration_since(*modified).is_ok_and(|age| age > max_age);
+        if index >= keep || expired {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SessionLog, prune_logs};
+    use crate::models::LogSource;
+    use chrono::Local;
+    use std::time::Duration;
+    use std::{env, fs, process};
+
+    #[test]
+    fn session_logs_are_tagged_and_pruned() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-logs-{}", process::id()));
+        let log = SessionLog::create_in(&dir, "/videos/demo take.mp4", Local::now())
+            .expect("log should be created");
+        log.write(LogSource::Stderr, "Using codec libx264");
+        let contents = fs::read_to_string(log.path()).expect("log should be readable");
+        assert!(log.path().to_string_lossy().ends_with("-demo_take.log"));
+        assert!(contents.contains("[supervisor] Recording to /videos/demo take.mp4"));
+        assert!(
+            contents
+                .lines()
+                .nth(1)
+                .is_some_and(|line| line.ends_with("[stderr] Using codec libx264"))
+        );
+
+        fs::write(dir.join("old.log"), b"").expect("file should be written");
+        fs::write(dir.join("notes.txt"), b"").expect("file should be written");
+        prune_logs(&dir, 1, Duration::from_secs(3600));
+        let remaining = fs::read_dir(&dir).expect("dir should be readable").count();
+        assert_eq!(remaining, 2);
+        assert!(dir.join("notes.txt").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
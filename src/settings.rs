@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app::SidebarState;
+use crate::paths::config_dir;
+use crate::profiles::{DEFAULT_PROFILE_NAME, ProfileStore};
+
+pub const SETTINGS_VERSION: u64 = 2;
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u64,
+    pub profiles: ProfileStore,
+    pub ui: UiSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            profiles: ProfileStore::default(),
+            ui: UiSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    pub dark_theme: bool,
+    pub sidebar_state: SidebarState,
+    pub allow_concurrent_sessions: bool,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            dark_theme: true,
+            sidebar_state: SidebarState::Expanded,
+            allow_concurrent_sessions: false,
+        }
+    }
+}
+
+pub fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SETTINGS_FILE_NAME))
+}
+
+pub fn load_settings() -> Result<Settings, String> {
+    let Some(path) = settings_path() else {
+        return Err("Cannot locate a config directory (HOME is not set).".to_string());
+    };
+    load_settings_from(&path)
+}
+
+pub fn load_settings_from(path: &Path) -> Result<Settings, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Settings::default()),
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+
+    match parse_settings(&contents) {
+        Ok(settings) => Ok(settings),
+        Err(err) => {
+            // Set the file aside so the next save cannot silently replace it.
+            let backup = path.with_extension("json.bak");
+            match fs::rename(path, &backup) {
+                Ok(()) => Err(format!(
+                    "Ignoring unreadable settings file (moved to {}): {err}",
+                    backup.display()
+                )),
+                Err(rename_err) => Err(format!(
+                    "Ignoring unreadable settings file {} (could not move it to {}: {rename_err}): {err}",
+                    path.display(),
+                    backup.display()
+                )),
+            }
+        }
+    }
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), String> {
+    let Some(path) = settings_path() else {
+        return Err("Cannot locate a config directory (HOME is not set).".to_string());
+    };
+    save_settings_to(&path, settings)
+}
+
+pub fn save_settings_to(path: &Path, settings: &Settings) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|err| format!("Failed to serialize settings: {err}"))?;
+
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .map_err(|err| format!("Failed to write {}: {err}", temp_path.display()))?;
+    fs::rename(&temp_path, path)
+        .map_err(|err| format!("Failed to replace {}: {err}", path.display()))
+}
+
+pub fn parse_settings(contents: &str) -> Result<Settings, String> {
+    let value: Value = serde_json::from_str(contents).map_err(|err| err.to_string())?;
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    let value = migrate(value, version);
+    let mut settings: Settings = serde_json::from_value(value).map_err(|err| err.to_string())?;
+    settings.version = SETTINGS_VERSION;
+    settings.profiles.normalize();
+    Ok(settings)
+}
+
+fn migrate(mut value: Value, from_version: u64) -> Value {
+    if from_version < 2 {
+        // Version 1 stored a single `config`; it becomes the default profile.
+        if let Some(object) = value.as_object_mut()
+            && let Some(config) = object.remove("config")
+        {
+            object.insert(
+                "profiles".to_string(),
+                serde_json::json!({
+                    "profiles": [{ "name": DEFAULT_PROFILE_NAME, "config": config }],
+                    "active": DEFAULT_PROFILE_NAME,
+                    "default_profile": DEFAULT_PROFILE_NAME,
+                }),
+            );
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SETTINGS_VERSION, Settings, load_settings_from, parse_settings};
+    use crate::app::SidebarState;
+    use crate::config::{AudioMode, CaptureMode, ParamEntry, RecorderConfig};
+    use crate::profiles::DEFAULT_PROFILE_NAME;
+    use std::{env, fs, process};
+
+    #[test]
+    fn settings_round_trip_through_json() {
+        let mut settings = Settings::default();
+        let config = RecorderConfig {
+            capture_mode: CaptureMode::Area,
+            audio_mode: AudioMode::Both,
+            codec_params: vec![ParamEntry {
+                key: "crf".to_string(),
+                value: "18".to_string(),
+            }],
+            ..RecorderConfig::default()
+        };
+        settings
+            .profiles
+            .create("gif", config)
+            .expect("profile should be created");
+        settings.ui.sidebar_state = SidebarState::Compact;
+        settings.ui.dark_theme = false;
+
+        let json = serde_json::to_string(&settings).expect("settings should serialize");
+        let parsed = parse_settings(&json).expect("settings should parse");
+        assert_eq!(parsed, settings);
+    }
+
+    #[test]
+    fn version_one_config_migrates_into_default_profile() {
+        let json = r#"{
+            "version": 1,
+            "config": { "codec": "libvpx-vp9", "some_future_field": 42 },
+            "future_section": {}
+        }"#;
+
+        let parsed = parse_settings(json).expect("settings should parse");
+        assert_eq!(parsed.version, SETTINGS_VERSION);
+        assert_eq!(parsed.profiles.names(), vec![DEFAULT_PROFILE_NAME]);
+        assert_eq!(parsed.profiles.default_profile, DEFAULT_PROFILE_NAME);
+        let config = parsed.profiles.active_config();
+        assert_eq!(config.codec, "libvpx-vp9");
+        assert_eq!(config.file_format, "mp4");
+        assert!(parsed.ui.dark_theme);
+    }
+
+    #[test]
+    fn unreadable_settings_are_moved_aside() {
+        let dir = env::temp_dir().join(format!("wf-recorder-ui-settings-{}", process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let path = dir.join("settings.json");
+        fs::write(&path, "{ not json").expect("settings should be written");
+
+        let backup = dir.join("settings.json.bak");
+        let err = load_settings_from(&path).expect_err("bad settings should be reported");
+        assert!(err.contains(&backup.display().to_string()));
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "{ not json");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+use crate::actions::{SimpleAction, run_simple_command};
+use crate::config::{CaptureMode, RecorderConfig};
+use crate::recorder::with_output_file;
+
+// Everything needed to reproduce the capture conditions of one output file.
+#[derive(Debug, Serialize)]
+pub struct RecordingMetadata {
+    pub file: String,
+    pub argv: Vec<String>,
+    pub profile: String,
+    pub started_at: String,
+    pub stopped_at: String,
+    pub duration_secs: f64,
+    pub exit_status: String,
+    pub capture: CaptureTarget,
+    pub wf_recorder_version: Option<String>,
+    pub config: RecorderConfig,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureTarget {
+    pub mode: String,
+    pub output: Option<String>,
+    pub geometry: Option<String>,
+    pub window_id: Option<String>,
+    pub window_app: Option<String>,
+    pub window_title: Option<String>,
+}
+
+impl RecordingMetadata {
+    pub fn new(
+        file: &str,
+        args: &[String],
+        config: &RecorderConfig,
+        profile: &str,
+        started: DateTime<Local>,
+        // Time actually recorded, so pauses since `started` do not count.
+        recorded: Duration,
+        exit_status: &str,
+    ) -> Self {
+        let args = with_output_file(args, file);
+        let window = |value: &str| {
+            (config.capture_mode == CaptureMode::Window && !value.trim().is_empty())
+                .then(|| value.trim().to_string())
+        };
+        let capture = CaptureTarget {
+            mode: config.capture_mode.as_str().to_string(),
+            output: arg_value(&args, "--output"),
+            geometry: arg_value(&args, "--geometry"),
+            window_id: window(&config.selected_window_id),
+            window_app: window(&config.selected_window_app),
+            window_title: window(&config.selected_window_title),
+        };
+        Self {
+            file: file.to_string(),
+            argv: std::iter::once("wf-recorder".to_string())
+                .chain(args)
+                .collect(),
+            profile: profile.to_string(),
+            started_at: started.to_rfc3339(),
+            stopped_at: Local::now().to_rfc3339(),
+            duration_secs: recorded.as_millis() as f64 / 1000.0,
+            exit_status: exit_status.to_string(),
+            capture,
+            wf_recorder_version: wf_recorder_version(),
+            config: config.clone(),
+        }
+    }
+
+    pub fn write(&self) -> Result<PathBuf, String> {
+        let path = sidecar_path(&self.file);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("Failed to serialize recording metadata: {err}"))?;
+        fs::write(&path, json)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+        Ok(path)
+    }
+}
+
+// `demo.mp4` → `demo.mp4.json`, so recordings differing only by container
+// never share a sidecar.
+pub fn sidecar_path(file: &str) -> PathBuf {
+    let path = Path::new(file);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".json");
+    path.with_file_name(name)
+}
+
+// Cached; first call runs `wf-recorder --version`.
+pub fn wf_recorder_version() -> Option<String> {
+    static VERSION: OnceLock<Option<String>> = OnceLock::new();
+    VERSION
+        .get_or_init(|| {
+            let output = run_simple_command(&SimpleAction::Version.args()).ok()?;
+            [output.stdout, output.stderr]
+                .iter()
+                .filter_map(|text| text.lines().map(str::trim).find(|line| !line.is_empty()))
+                .map(str::to_string)
+                .next()
+        })
+        .clone()
+}
+
+// Warms the cache off the UI thread so the sidecar write never waits on it.
+pub fn prefetch_wf_recorder_version() {
+    thread::spawn(wf_recorder_version);
+}
+
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordingMetadata, sidecar_path};
+    use crate::config::{CaptureMode, RecorderConfig};
+    use chrono::Local;
+    use std::path::Path;
+    use std::time::Duration;
+
+    #[test]
+    fn metadata_records_argv_and_capture_target() {
+        let config = RecorderConfig {
+            capture_mode: CaptureMode::Window,
+            selected_window_id: "42".to_string(),
+            selected_window_app: "firefox".to_string(),
+            ..RecorderConfig::default()
+        };
+        let args: Vec<String> = ["--geometry", "0,0 640x480", "--file", "/tmp/a.mp4"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let metadata = RecordingMetadata::new(
+            "/tmp/b.mp4",
+            &args,
+            &config,
+            "demo",
+            Local::now() - chrono::Duration::milliseconds(4000),
+            Duration::from_millis(2500),
+            "exit code 0",
+        );
+
+        assert_eq!(metadata.argv[0], "wf-recorder");
+        assert_eq!(metadata.argv.last().map(String::as_str), Some("/tmp/b.mp4"));
+        assert_eq!(metadata.capture.geometry.as_deref(), Some("0,0 640x480"));
+        assert_eq!(metadata.capture.output, None);
+        assert_eq!(metadata.capture.window_app.as_deref(), Some("firefox"));
+        assert_eq!(metadata.capture.window_title, None);
+        assert_eq!(metadata.duration_secs, 2.5);
+
+        let json = serde_json::to_value(&metadata).expect("metadata should serialize");
+        assert_eq!(json["config"]["capture_mode"], "window");
+        assert_eq!(
+            sidecar_path("/tmp/b.mp4"),
+            Path::new("/tmp/b.mp4.json").to_path_buf()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::recovery::run_ffmpeg;
+
+const THUMBNAIL_WIDTH: u32 = 240;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CutMode {
+    // Cuts land on the nearest keyframe before each point; fast and lossless.
+    Copy,
+    // Frame-exact, re-encoded with ffmpeg's default encoders for the container.
+    Reencode,
+}
+
+impl CutMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            CutMode::Copy => "stream copy",
+            CutMode::Reencode => "re-encoded",
+        }
+    }
+}
+
+// `demo.mp4` → `demo.trimmed.mp4`, or `demo.trimmed-2.mp4` if that is taken.
+// `extension` replaces the source's when set.
+pub fn derived_path(file: &str, label: &str, extension: Option<&str>) -> String {
+    let path = Path::new(file);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "recording".to_string());
+    let extension = match extension {
+        Some(extension) => format!(".{extension}"),
+        None => path
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default(),
+    };
+    (1..)
+        .map(|index| match index {
+            1 => format!("{stem}.{label}{extension}"),
+            index => format!("{stem}.{label}-{index}{extension}"),
+        })
+        .map(|name| path.with_file_name(name).to_string_lossy().into_owned())
+        .find(|candidate| !Path::new(candidate).exists())
+        .unwrap_or_default()
+}
+
+pub fn trim_args(source: &str, output: &str, start: f64, end: f64, mode: CutMode) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-ss".to_string(),
+        format!("{start:.3}"),
+        "-i".to_string(),
+        source.to_string(),
+        "-t".to_string(),
+        format!("{:.3}", end - start),
+    ];
+    if mode == CutMode::Copy {
+        args.extend(
+            ["-map", "0", "-c", "copy", "-avoid_negative_ts", "make_zero"]
+                .into_iter()
+                .map(str::to_string),
+        );
+    }
+    args.push(output.to_string());
+    args
+}
+
+// Tries a stream copy first unless `exact` is set, and re-encodes if the copy
+// fails. Returns the mode that produced `output`.
+pub fn trim(
+    source: &str,
+    output: &str,
+    start: f64,
+    end: f64,
+    exact: bool,
+) -> Result<CutMode, String> {
+    if start < 0.0 || end <= start {
+        return Err("The end of the cut must come after its start.".to_string());
+    }
+    let copy_error = if exact {
+        None
+    } else {
+        match run_ffmpeg(&trim_args(source, output, start, end, CutMode::Copy)) {
+            Ok(()) => return Ok(CutMode::Copy),
+            Err(err) => {
+                let _ = fs::remove_file(output);
+                Some(err)
+            }
+        }
+    };
+    run_ffmpeg(&trim_args(source, output, start, end, CutMode::Reencode))
+        .map(|()| CutMode::Reencode)
+        .map_err(|err| {
+            let _ = fs::remove_file(output);
+            match copy_error {
+                Some(copy_error) => format!(
+                    "ffmpeg could not trim {source} by stream copy{copy_error}, nor by re-encoding{err}"
+                ),
+                None => format!("ffmpeg could not trim {source}{err}"),
+            }
+        })
+}
+
+// One PNG frame at `at` seconds, scaled down for previews.
+pub fn thumbnail(file: &str, at: f64) -> Result<Vec<u8>, String> {
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-ss"])
+        .arg(format!("{:.3}", at.max(0.0)))
+        .args(["-i", file, "-frames:v", "1", "-vf"])
+        .arg(format!("scale={THUMBNAIL_WIDTH}:-2"))
+        .args(["-f", "image2pipe", "-c:v", "png", "-"])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("Cannot run ffmpeg: {err}"))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => "No frame at this position.".to_string(),
+            reason => reason.to_string(),
+        });
+    }
+    Ok(output.stdout)
+}
+
+// `62.5` → `1:02.50`.
+pub fn format_timestamp(seconds: f64) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u64;
+    let (minutes, centis) = (centis / 6000, centis % 6000);
+    match minutes {
+        0..=59 => format!("{minutes}:{:02}.{:02}", centis / 100, centis % 100),
+        _ => format!(
+            "{}:{:02}:{:02}.{:02}",
+            minutes / 60,
+            minutes % 60,
+            centis / 100,
+            centis % 100
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CutMode, derived_path, format_timestamp, trim_args};
+
+    #[test]
+    fn trims_copy_streams_and_name_the_result_after_the_source() {
+        let args = trim_args(
+            "/v/demo.mp4",
+            "/v/demo.trimmed.mp4",
+            2.0,
+            75.25,
+            CutMode::Copy,
+        );
+        assert_eq!(
+            args.join(" "),
+            "-ss 2.000 -i /v/demo.mp4 -t 73.250 -map 0 -c copy -avoid_negative_ts make_zero /v/demo.trimmed.mp4"
+        );
+        let args = trim_args("/v/demo.mp4", "/v/out.mp4", 1.5, 3.0, CutMode::Reencode);
+        assert!(!args.contains(&"copy".to_string()));
+
+        assert_eq!(
+            derived_path("/nonexistent/demo.mp4", "trimmed", None),
+            "/nonexistent/demo.trimmed.mp4"
+        );
+        assert_eq!(
+            derived_path("/nonexistent/demo.mkv", "web", Some("mp4")),
+            "/nonexistent/demo.web.mp4"
+        );
+        assert_eq!(format_timestamp(62.5), "1:02.50");
+        assert_eq!(format_timestamp(3725.0), "1:02:05.00");
+    }
+}
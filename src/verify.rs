@@ -0,0 +1,185 @@
+use serde_json::Value;
+use std::fs;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::config::{AudioMode, RecorderConfig};
+
+// What a finished file should contain, judged from the settings it was
+// recorded with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expectations {
+    pub duration: Duration,
+    pub audio: bool,
+    pub video_codec: Option<String>,
+}
+
+impl Expectations {
+    pub fn from_config(config: &RecorderConfig, duration: Duration) -> Self {
+        Self {
+            duration,
+            audio: config.audio_mode != AudioMode::None,
+            video_codec: expected_codec_name(&config.codec),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProbeReport {
+    pub duration: Option<f64>,
+    pub video_codecs: Vec<String>,
+    pub audio_codecs: Vec<String>,
+}
+
+// Returns warnings; an empty list means the file looks fine.
+pub fn verify_recording(file: &str, expectations: &Expectations) -> Vec<String> {
+    match fs::metadata(file) {
+        Ok(metadata) if metadata.len() == 0 => {
+            return vec!["The file is empty (0 bytes).".to_string()];
+        }
+        Ok(_) => {}
+        Err(err) => return vec![format!("Cannot read the file: {err}")],
+    }
+    match probe(file) {
+        Ok(report) => check(&report, expectations),
+        Err(err) => vec![err],
+    }
+}
+
+pub fn probe(file: &str) -> Result<ProbeReport, String> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_entries",
+            "format=duration:stream=codec_type,codec_name",
+            file,
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("Skipped the integrity check: cannot run ffprobe ({err})."))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        let reason = stderr
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("no details");
+        return Err(format!(
+            "ffprobe cannot read the file, it is probably truncated or corrupt: {reason}"
+        ));
+    }
+    parse_probe(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub fn parse_probe(json: &str) -> Result<ProbeReport, String> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|err| format!("Cannot understand ffprobe output: {err}"))?;
+    let mut report = ProbeReport {
+        duration: value["format"]["duration"]
+            .as_str()
+            .and_then(|duration| duration.parse().ok()),
+        ..ProbeReport::default()
+    };
+    for stream in value["streams"].as_array().into_iter().flatten() {
+        let codec = stream["codec_name"]
+            .as_str()
+            .unwrap_or("unknown")
+            .to_string();
+        match stream["codec_type"].as_str() {
+            Some("video") => report.video_codecs.push(codec),
+            Some("audio") => report.audio_codecs.push(codec),
+            _ => {}
+        }
+    }
+    Ok(report)
+}
+
+pub fn check(report: &ProbeReport, expectations: &Expectations) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if report.video_codecs.is_empty() {
+        warnings.push("No video stream found.".to_string());
+    } else if let Some(expected) = &expectations.video_codec
+        && !report.video_codecs.contains(expected)
+    {
+        warnings.push(format!(
+            "Video codec is {}, expected {expected}.",
+            report.video_codecs.join(", ")
+        ));
+    }
+    if expectations.audio && report.audio_codecs.is_empty() {
+        warnings.push("Audio was enabled but the file has no audio track.".to_string());
+    }
+
+    let expected = expectations.duration.as_secs_f64();
+    match report.duration {
+        // Startup and finalizing make the two differ a little; a big gap
+        // usually means lost frames or a truncated file.
+        Some(duration) if duration > 0.0 => {
+            if (duration - expected).abs() > (expected * 0.1).max(3.0) {
+                warnings.push(format!(
+                    "The file plays for {duration:.1}s but recording ran for {expected:.1}s."
+                ));
+            }
+        }
+        _ => warnings.push("The file reports no duration.".to_string()),
+    }
+    warnings
+}
+
+// Maps a wf-recorder/ffmpeg encoder name to the codec name ffprobe reports.
+pub fn expected_codec_name(encoder: &str) -> Option<String> {
+    let encoder = encoder.trim().to_ascii_lowercase();
+    let codec = match encoder.as_str() {
+        "" => return None,
+        "libx264" | "libx264rgb" | "libopenh264" => "h264",
+        "libx265" => "hevc",
+        "libvpx" => "vp8",
+        "libvpx-vp9" => "vp9",
+        "libaom-av1" | "libsvtav1" | "librav1e" => "av1",
+        other => match other.split_once('_') {
+            // Hardware encoders: h264_vaapi, hevc_nvenc, av1_qsv, ...
+            Some((codec, _)) => return Some(codec.to_string()),
+            None => other,
+        },
+    };
+    Some(codec.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expectations, check, expected_codec_name, parse_probe};
+    use std::time::Duration;
+
+    #[test]
+    fn probe_reports_are_checked_against_the_recording_settings() {
+        let report = parse_probe(
+            r#"{
+                "streams": [{ "codec_name": "h264", "codec_type": "video" }],
+                "format": { "duration": "9.480000" }
+            }"#,
+        )
+        .expect("probe output should parse");
+        let mut expectations = Expectations {
+            duration: Duration::from_secs(10),
+            audio: false,
+            video_codec: expected_codec_name("h264_vaapi"),
+        };
+        assert_eq!(check(&report, &expectations), Vec::<String>::new());
+
+        expectations.audio = true;
+        expectations.duration = Duration::from_secs(60);
+        expectations.video_codec = expected_codec_name("libvpx-vp9");
+        let warnings = check(&report, &expectations);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("expected vp9"));
+        assert!(warnings[1].contains("no audio track"));
+        assert!(warnings[2].contains("9.5s"));
+
+        let empty = parse_probe(r#"{ "format": {} }"#).expect("probe output should parse");
+        assert!(check(&empty, &expectations)[0].contains("No video stream"));
+        assert_eq!(expected_codec_name(""), None);
+    }
+}